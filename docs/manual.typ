@@ -143,6 +143,13 @@ armado en Typst.
 #show: init_rcsection
 ```
 
+Mientras se redacta el documento, `draft: true` muestra bajo cada dibujo las advertencias del
+plugin, por ejemplo barras que se superponen o un recubrimiento no definido:
+
+```typ
+#show: init_rcsection.with(draft: true)
+```
+
 = Sintaxis
 Para representar un elemento estructural se define un encabezado seguido de dos puntos (`:`)
 y un bloque indentado con las propiedades:
@@ -174,6 +181,16 @@ set:
   <propiedad global> <valor>
 ```
 
+=== Unidades
+Define la unidad de las longitudes escritas sin sufijo y de los textos de cotas y etiquetas.
+
+```
+units <mm | cm | m | in>
+```
+_Valor por defecto: `cm`_
+
+Cualquier longitud puede llevar su propia unidad como sufijo, con o sin espacio: `cover 40mm`, `span 4.5 m`, `rto@8in`.
+
 === Escala de dibujo
 Define la escala de dibujo para los tipos de vista. Se representa como una relación.
 
@@ -186,9 +203,15 @@ _Valor por defecto: `1:20`_
 Habilita la gráfica de las cotas para las dimensiones de la sección.
 
 ```
-dims <on | off>
+dims <on | off | full>
 ```
-_Valor por defecto: `off`_
+
+#table(
+  columns: (1fr, 3fr),
+  [`off`], [Sin cotas \ _valor por defecto_],
+  [`on`], [Ancho y alto de la sección, luz y apoyos en la vista longitudinal],
+  [`full`], [Además, recubrimiento y espaciamiento del acero inferior],
+)
 
 === Etiquetas
 Habilita la gráfica de las etiquetas para los aceros de refuerzo.
@@ -205,6 +228,7 @@ Los modos soportados son:
   [`callout`], [Cantidad y tamaño de acero con flechas],
   [`legend`], [Una leyenda debajo del gráfico con cantidad y tamaño de acero],
   [`both`], [Modos `callout` y `legend` activados],
+  [`table`], [Una tabla debajo del gráfico con cantidad, diámetro y área de acero de cada grupo],
 )
 
 == Tipos de secciones
@@ -231,6 +255,64 @@ Para la definición de la geometría de una sección (por default en cm), se tie
   [`T ancho_total alto_total espesor_ala espesor_alma `], [Define una sección en T \ _ejemplo: `T 60 60 20 30`_],
   [`L ancho_total alto_total espesor_ala espesor_alma `], [Define una sección en L \ _ejemplo: `L 50 50 15 25`_],
   [`cover valor`], [Valor del recubrimiento \ _ejemplo: `cover 2`_],
+  [`cover top valor bot valor sides valor`], [Recubrimiento por cara \ _ejemplo: `cover top 2.5 bot 5 sides 4`_],
+)
+
+En el recubrimiento por cara, las caras omitidas toman el valor inicial: `cover 4 bot 5` usa 5 en la
+cara inferior y 4 en las demás. Las secciones circulares usan un solo valor. A lo largo del elemento,
+las barras y los estribos terminan a la distancia del recubrimiento lateral de cada extremo.
+
+Todas las dimensiones deben ser mayores que cero, y el recubrimiento debe ser menor que la mitad
+de la menor dimensión de la sección (del alma en secciones T y L); los recubrimientos superior e
+inferior juntos deben ser menores que la altura.
+
+=== Polígonos
+Para cajones, pilares huecos y piezas prefabricadas, la forma se define con un bloque `shape:` en
+lugar de la primera línea. El contorno es una lista de vértices, uno por línea, en cualquier sentido; cada
+hueco (`void`, con un nombre opcional) tiene su propio `polygon:`. Las coordenadas admiten signo,
+decimales y unidades:
+
+```
+beam "Cajón-P1":
+    shape:
+        polygon:
+            (0, 0)
+            (200, 0)
+            (200, 120)
+            (0, 120)
+        void "celda":
+            polygon:
+                (20, 20)
+                (180, 20)
+                (180, 95)
+                (20, 95)
+    cover 5
+    top 10 #8
+    bot #8 @ 15
+```
+
+Cada polígono necesita al menos tres vértices y encerrar un área. Un vértice no puede repetir el
+anterior, ni el último el primero: el polígono se cierra solo. Cada hueco debe quedar dentro del
+contorno, y puede tocarlo. El estribo sigue el contorno al
+recubrimiento de cada cara: superior en las caras que miran hacia arriba, inferior en las que miran hacia
+abajo y lateral en las demás. Las capas `top` y `bot` se reparten a lo ancho del contorno en el punto
+más alto y más bajo; `perim` ubica una barra en cada vértice y reparte las demás en los lados más largos,
+o, si hay menos barras que vértices, las espacia por igual a lo largo del contorno. Las barras sueltas se
+miden desde la caja que envuelve el contorno o desde el centroide descontando los huecos. Las zonas
+`mid`, `sides` y `corners` y los estribos que no son `outer` no se dibujan en polígonos.
+
+== Materiales
+Resistencias y módulos de elasticidad. Se pueden definir en una sección o en el bloque `set:`.
+Sin unidad, los valores están en kgf/cm²; también se aceptan `kgf/cm2`, `MPa`, `GPa`, `psi` y `ksi`.
+
+#table(
+  columns: (1fr, 3fr),
+  [`fc valor`], [Resistencia del concreto \ _ejemplo: `fc 280`, `fc 21 MPa`_],
+  [`fc C fck/fcu`], [Clase de resistencia del Eurocódigo \ _ejemplo: `fc C25/30`_],
+  [`fy valor`], [Fluencia del acero \ _ejemplo: `fy 4200`, `fy 60 ksi`_],
+  [`fy Grade valor`], [Grado ASTM, en ksi hasta 100 y en MPa sobre 100 \ _ejemplo: `fy Grade 60`_],
+  [`Es valor`], [Módulo de elasticidad del acero \ _ejemplo: `Es 200 GPa`_],
+  [`Ec valor`], [Módulo de elasticidad del concreto \ _ejemplo: `Ec 250000`_],
 )
 
 == Propiedades para el acero longitudinal
@@ -251,15 +333,110 @@ Las valores para definir zonas son:
   [`mid`], [Acero en la zona media \ _Ejemplo: `mid 2 3/4"`_],
   [`sides`], [Acero en los lados izquierdo y derecho \ _Ejemplo: `sides 2 #5`_],
   [`perim`], [Distribución perimetral equitativa (Para columnas) \ _Ejemplo: `perim 7 1"`_],
+  [`corners`], [4 barras en los dobleces del estribo, en el orden superior izquierda, superior derecha, inferior derecha e inferior izquierda (Para secciones rectangulares) \ _Ejemplo: `corners 4 1"`_],
 )
 
+Con `corners`, las barras de la primera capa `top` y `bot`, las de `sides` y las de `perim` se
+distribuyen entre las barras de esquina.
+
+Las secciones circulares solo admiten `perim`, `mid` y barras sueltas; las demás zonas no se dibujan en
+ninguna de las vistas y se advierten.
+
+Los dobleces del estribo usan el diámetro interior normalizado: 4 diámetros del estribo hasta el `#5`
+(16 mm) y 6 diámetros para estribos mayores, o el diámetro de la barra más gruesa apoyada en el doblez
+si es mayor. Las barras extremas de la capa junto al estribo y las esquinas de `perim` se apoyan en el
+doblez, y las demás barras de la capa se reparten entre ellas.
+
+=== Capas
+Varias líneas `top` o `bot` forman capas: las líneas `top` se apilan hacia abajo en el orden en que se
+escriben y las líneas `bot` hacia arriba, de modo que la última línea `bot` descansa sobre el estribo.
+
+Una capa también se puede declarar explícitamente, numerada desde 1 junto al estribo, con sus barras
+en un bloque indentado:
+
+```
+bot 3 1"
+layer bot 2:
+  2 3/4"
+```
+
+Cada capa se ubica a partir de la barra más grande de la capa anterior, más una separación libre de
+2.5 cm que se puede cambiar en una sección o en el bloque `set:`:
+
+```
+layer spacing <longitud>
+```
+
 === Cantidad
-Número de aceros
+Número de aceros, entero y mayor que cero.
+
+En muros y losas, las líneas `top`, `bot` y `mid` también se pueden dar por su separación entre ejes
+en lugar de la cantidad:
+
+```
+<zona> <tamaño> @ <separación>
+```
+
+La cantidad de barras es la que entra en el ancho disponible después del recubrimiento y el estribo,
+y el sobrante se reparte por igual en ambos extremos. Entre barras de esquina se usa la menor cantidad
+que no supere la separación. Las etiquetas muestran el tamaño y la separación, por ejemplo `Ø1/2"@20`.
+
+_Ejemplo: `top 1/2" @ 20`, `bot #4 @ 25`_
+
+En esta forma los tamaños mixtos se escriben con guion, como `1-3/8"`, porque `1 3/8"` se leería como
+una barra de 3/8".
 
 === Tamaño
-Soporta la notación por pulgadas y estándar.
+Los tamaños se buscan en el catálogo de barras del documento, que define el diámetro, el área y el peso nominal de cada barra.
+Un tamaño que no existe en el catálogo es un error.
+
+_Ejemplo: `#3`, `1/2"`, `1 3/8"`, `1-3/8"`_
 
-_Ejemplo: `#3`, `1/2"`_
+El catálogo se elige en el bloque `set:`:
+
+```
+catalog <imperial | metric | soft-metric>
+```
+
+#table(
+  columns: (1fr, 3fr),
+  [`imperial`], [ASTM A615, `#3` a `#18` o en pulgadas \ _valor por defecto_],
+  [`metric`], [Barras de 6 a 40 mm: `Ø16` o `16mm`],
+  [`soft-metric`], [ASTM A615M, `#10` a `#57`],
+)
+
+=== Separación
+Se verifica la separación libre entre barras. Dentro de una misma línea debe ser al menos el mayor
+entre el diámetro de la barra, 2.5 cm y 4/3 del tamaño máximo del agregado; entre capas, 2.5 cm.
+Las barras que no cumplen se informan como advertencias (ver `draft`), sin dejar de dibujar la sección.
+
+El tamaño máximo del agregado se define en una sección o en el bloque `set:`:
+
+```
+aggregate <longitud>
+```
+
+_Ejemplo: `aggregate 2.5`, `aggregate 19mm`_
+
+=== Barras sueltas
+Para detalles irregulares, una barra se puede ubicar en una posición exacta. Las coordenadas admiten
+signo y decimales:
+
+```
+bar <tamaño> at (<x>, <y>)
+```
+
+_Ejemplo: `bar 1" at (5, 55)`, `bar #6 at (-7.5, 0)`_
+
+Las coordenadas se miden desde la esquina inferior izquierda de la sección, o desde su centroide, según
+se indique en una sección o en el bloque `set:`:
+
+```
+origin <centroid | bottom-left>
+```
+
+Estas barras se verifican junto con las demás (superposición y separación), y se advierte si alguna
+queda fuera del concreto o dentro de un hueco.
 
 == Propiedades para el acero transversal
 Define el confinamiento de la sección y su espaciamiento.
@@ -270,9 +447,9 @@ ties <tamaño> <distribución>
 ```
 
 === Tamaño
-Soporta la notación por pulgadas y estándar.
+Igual que para el acero longitudinal, según el catálogo del documento.
 
-_Ejemplo: `#3`, `1/2"`_
+_Ejemplo: `#3`, `3/8"`, `Ø8`_
 
 === Distribución
 Es una secuencia separada por espacios
@@ -282,6 +459,62 @@ Es una secuencia separada por espacios
 
 _Ejemplo: `1@5 4@10 rto@20`_
 
+=== Tipos de estribos
+Un bloque `ties:` combina varios estribos, cada uno con su tamaño y su distribución. Debe tener un
+único estribo exterior (`outer`), que define la posición de las barras; los estribos interiores (`inner`)
+se apoyan en su cara interna.
+
+```
+ties:
+  outer #3 1@5 6@10 rto@15
+  inner cross #3 1@5 rto@15
+```
+
+#table(
+  columns: (1fr, 3fr),
+  [`outer`], [Estribo cerrado alrededor de todas las barras \ _equivale a `ties <tamaño> <distribución>`_],
+  [`outer open-top`], [Estribo en U abierto arriba (Para losas)],
+  [`outer overlapping`], [Dos estribos cerrados que se traslapan en el centro (Para columnas anchas)],
+  [`inner diamond`], [Estribo en rombo por la barra central de cada cara],
+  [`inner cross`], [Un gancho suplementario en cada dirección, con gancho de 135° en un extremo y de 90°
+    en el otro],
+)
+
+Los estribos interiores pasan por las barras de las caras: cada gancho suplementario une dos barras
+enfrentadas en caras opuestas, y el rombo toca la barra más cercana al centro de cada cara, sin contar
+las de las esquinas. Si faltan esas barras se emite la advertencia `tie-without-bars`.
+
+En la vista longitudinal cada estribo se dibuja con su propia distribución. Las secciones circulares
+solo admiten `outer`.
+
+==== Ganchos
+Los estribos cerrados terminan en dos ganchos de 135° hacia el núcleo, de 6 diámetros del estribo
+(mínimo 7.5 cm), en la esquina superior izquierda. Otra esquina se indica con `hook` al final de la
+línea:
+
+```
+ties #3 1@5 rto@20 hook bottom-right
+```
+
+La esquina puede ser `top-left`, `top-right`, `bottom-right` o `bottom-left`, y se aplica a `outer` y a
+`outer overlapping`. En los ganchos suplementarios (`inner cross`), `hook 90` o `hook 135` usa ese
+ángulo en ambos extremos.
+
+=== Espiral
+Las secciones circulares pueden llevar una espiral continua en lugar de estribos:
+
+```
+spiral <tamaño> pitch <paso>
+```
+
+_Ejemplo: `spiral #3 pitch 5`_
+
+En la vista longitudinal la espiral se dibuja como líneas inclinadas, media vuelta cada una, con 1.5
+vueltas adicionales cerradas en cada extremo para su anclaje. Las vueltas intermedias se reparten por
+igual sin superar el paso. Debajo de la vista se indica el número de vueltas y la longitud de la barra.
+Si la luz no alcanza para las vueltas de los extremos, la espiral no se dibuja y se emite la advertencia
+`spiral-too-short`.
+
 == Ejemplos
 
 #show: init_rcsection
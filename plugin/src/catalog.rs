@@ -0,0 +1,158 @@
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+/// Table of bar sizes a document is written against, chosen with `catalog`.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub enum Catalog {
+    /// ASTM A615: `#3` to `#18`, or inches such as `3/4"` and `1 3/8"`
+    #[default]
+    Imperial,
+    /// 6 to 40 mm: `Ø16` or `16mm`
+    Metric,
+    /// ASTM A615M: `#10` to `#57`
+    SoftMetric,
+}
+
+/// Nominal properties of a bar size.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct Bar {
+    pub diameter: f64, // cm
+    pub area: f64,     // cm²
+    pub weight: f64,   // kg/m
+}
+
+struct Entry {
+    names: &'static [&'static str],
+    bar: Bar,
+}
+
+const fn entry(names: &'static [&'static str], diameter: f64, area: f64, weight: f64) -> Entry {
+    Entry {
+        names,
+        bar: Bar {
+            diameter,
+            area,
+            weight,
+        },
+    }
+}
+
+const IMPERIAL: [Entry; 11] = [
+    entry(&["#3", "3/8\""], 0.9525, 0.71, 0.560),
+    entry(&["#4", "1/2\""], 1.27, 1.29, 0.994),
+    entry(&["#5", "5/8\""], 1.5875, 2.00, 1.552),
+    entry(&["#6", "3/4\""], 1.905, 2.84, 2.235),
+    entry(&["#7", "7/8\""], 2.2225, 3.87, 3.042),
+    entry(&["#8", "1\""], 2.54, 5.10, 3.973),
+    entry(&["#9", "1 1/8\""], 2.8651, 6.45, 5.060),
+    entry(&["#10", "1 1/4\""], 3.2258, 8.19, 6.404),
+    entry(&["#11", "1 3/8\""], 3.5814, 10.06, 7.907),
+    entry(&["#14", "1 3/4\""], 4.3002, 14.52, 11.384),
+    entry(&["#18", "2 1/4\""], 5.7328, 25.81, 20.239),
+];
+
+const METRIC: [Entry; 14] = [
+    entry(&["Ø6", "6mm"], 0.6, 0.283, 0.222),
+    entry(&["Ø8", "8mm"], 0.8, 0.503, 0.395),
+    entry(&["Ø10", "10mm"], 1.0, 0.785, 0.617),
+    entry(&["Ø12", "12mm"], 1.2, 1.131, 0.888),
+    entry(&["Ø14", "14mm"], 1.4, 1.539, 1.208),
+    entry(&["Ø16", "16mm"], 1.6, 2.011, 1.578),
+    entry(&["Ø18", "18mm"], 1.8, 2.545, 1.998),
+    entry(&["Ø20", "20mm"], 2.0, PI, 2.466),
+    entry(&["Ø22", "22mm"], 2.2, 3.801, 2.984),
+    entry(&["Ø25", "25mm"], 2.5, 4.909, 3.853),
+    entry(&["Ø28", "28mm"], 2.8, 6.158, 4.834),
+    entry(&["Ø32", "32mm"], 3.2, 8.042, 6.313),
+    entry(&["Ø36", "36mm"], 3.6, 10.179, 7.990),
+    entry(&["Ø40", "40mm"], 4.0, 12.566, 9.865),
+];
+
+// Same bars as the imperial table, designated by their diameter in mm
+const SOFT_METRIC: [Entry; 11] = [
+    entry(&["#10"], 0.95, 0.71, 0.560),
+    entry(&["#13"], 1.27, 1.29, 0.994),
+    entry(&["#16"], 1.59, 1.99, 1.552),
+    entry(&["#19"], 1.91, 2.84, 2.235),
+    entry(&["#22"], 2.22, 3.87, 3.042),
+    entry(&["#25"], 2.54, 5.10, 3.973),
+    entry(&["#29"], 2.87, 6.45, 5.060),
+    entry(&["#32"], 3.23, 8.19, 6.404),
+    entry(&["#36"], 3.58, 10.06, 7.907),
+    entry(&["#43"], 4.30, 14.52, 11.384),
+    entry(&["#57"], 5.73, 25.81, 20.239),
+];
+
+/// Inside diameter of a standard bend of a tie, in cm: 4db up to #5 (16mm)
+/// and 6db above.
+pub fn tie_bend_diameter(diameter: f64) -> f64 {
+    // Largest diameter of the range across the catalogs, Ø16 for #5
+    let factor = if diameter <= 1.6 { 4.0 } else { 6.0 };
+    factor * diameter
+}
+
+impl Catalog {
+    pub fn name(self) -> &'static str {
+        match self {
+            Catalog::Imperial => "imperial",
+            Catalog::Metric => "metric",
+            Catalog::SoftMetric => "soft-metric",
+        }
+    }
+
+    /// Looks up a size as written in the document. Mixed fractions may use a
+    /// space or a hyphen, e.g. `1 3/8"` or `1-3/8"`.
+    pub fn find(self, size: &str) -> Option<Bar> {
+        let size = size.replace('-', " ").replace('ø', "Ø");
+        self.entries()
+            .iter()
+            .find(|e| e.names.contains(&size.as_str()))
+            .map(|e| e.bar)
+    }
+
+    fn entries(self) -> &'static [Entry] {
+        match self {
+            Catalog::Imperial => &IMPERIAL,
+            Catalog::Metric => &METRIC,
+            Catalog::SoftMetric => &SOFT_METRIC,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_sizes() {
+        let bar = |catalog: Catalog, size| catalog.find(size).map(|b| b.diameter);
+
+        assert_eq!(
+            bar(Catalog::Imperial, "#6"),
+            bar(Catalog::Imperial, "3/4\"")
+        );
+        assert_eq!(bar(Catalog::Imperial, "#9"), Some(2.8651));
+        assert_eq!(bar(Catalog::Imperial, "1-3/8\""), Some(3.5814));
+        assert_eq!(bar(Catalog::Imperial, "1 3/8\""), Some(3.5814));
+        assert_eq!(bar(Catalog::Metric, "Ø16"), Some(1.6));
+        assert_eq!(bar(Catalog::Metric, "16mm"), Some(1.6));
+        assert_eq!(bar(Catalog::SoftMetric, "#10"), Some(0.95));
+
+        // Each catalog only knows its own designations
+        assert_eq!(bar(Catalog::Imperial, "#99"), None);
+        assert_eq!(bar(Catalog::Imperial, "7/3\""), None);
+        assert_eq!(bar(Catalog::Metric, "#6"), None);
+        assert_eq!(bar(Catalog::SoftMetric, "#6"), None);
+    }
+
+    #[test]
+    fn test_bend_diameters() {
+        let bend = |catalog: Catalog, size| tie_bend_diameter(catalog.find(size).unwrap().diameter);
+
+        assert_eq!(bend(Catalog::Imperial, "#5"), 4.0 * 1.5875);
+        assert_eq!(bend(Catalog::Metric, "Ø16"), 4.0 * 1.6);
+        assert_eq!(bend(Catalog::SoftMetric, "#16"), 4.0 * 1.59);
+        assert_eq!(bend(Catalog::Imperial, "#6"), 6.0 * 1.905);
+        assert_eq!(bend(Catalog::Metric, "Ø20"), 6.0 * 2.0);
+    }
+}
@@ -1,7 +1,21 @@
-use crate::parser::ast::{RebarPattern, Section, Shape, View};
+mod annotations;
+mod checks;
+mod polygon;
+mod ties;
+
+use crate::catalog::tie_bend_diameter;
+use crate::parser::ast::{
+    BarGroup, Cover, Defaults, Dims, Labels, Origin, RebarEntry, RebarPattern, Section,
+    SectionProperties, Shape, StirrupKind, View,
+};
+use crate::parser::{Severity, Warning};
+use checks::warning;
 use serde::{Deserialize, Serialize};
 use std::f64::consts::PI;
 
+/// Clear distance between layers of bars when `layer spacing` is not given, in cm.
+const LAYER_SPACING: f64 = 2.5;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Drawing {
     pub id: Option<String>,
@@ -40,6 +54,17 @@ pub enum Primitive {
         x: f64,
         y: f64,
         content: String,
+        anchor: Option<String>, // e.g. "west"; centered when unset
+    },
+    // Measures from `start` to `end`; the dimension line is drawn `offset`
+    // away along the left-hand normal of that direction, with the text on it.
+    Dimension {
+        start: (f64, f64),
+        end: (f64, f64),
+        offset: f64,
+        text: String,
+        stroke: Option<Stroke>,
+        group: Option<String>,
     },
 }
 
@@ -63,9 +88,30 @@ impl Drawing {
     }
 }
 
-pub fn generate(section: &Section) -> Vec<Drawing> {
+/// Drawings of a section. Anything drawn differently from what was probably
+/// meant is reported in `warnings`.
+pub fn generate(
+    section: &Section,
+    defaults: &Defaults,
+    warnings: &mut Vec<Warning>,
+) -> Vec<Drawing> {
     let mut drawings = Vec::new();
-    let props = &section.properties;
+    let props = &section.properties.with_defaults(defaults);
+    let units = defaults.units.unwrap_or_default();
+
+    if props.cover.is_none() {
+        warnings.push(warning(
+            section,
+            section.line,
+            Severity::Info,
+            "default-cover",
+            format!(
+                "no `cover` given, drawn with {}{}",
+                annotations::format_length(4.0, units),
+                units.suffix()
+            ),
+        ));
+    }
 
     // Determine which views to generate
     let show_section = match &props.view {
@@ -115,81 +161,80 @@ pub fn generate(section: &Section) -> Vec<Drawing> {
                         group: Some("concrete".to_string()),
                     });
                 }
+                Shape::Tee { .. } | Shape::Ell { .. } => {
+                    section_drawing.add(Primitive::Path {
+                        points: outline(shape),
+                        closed: true,
+                        stroke: Some(Stroke {
+                            color: "black".to_string(),
+                            width: 1.0,
+                        }),
+                        fill: None,
+                        group: Some("concrete".to_string()),
+                    });
+                }
+                // The outline, then the edge of every void
+                Shape::Polygon { outer, voids } => {
+                    let (outer, voids) = polygon::centered(outer, voids);
+                    for points in std::iter::once(outer).chain(voids) {
+                        section_drawing.add(Primitive::Path {
+                            points,
+                            closed: true,
+                            stroke: Some(Stroke {
+                                color: "black".to_string(),
+                                width: 1.0,
+                            }),
+                            fill: None,
+                            group: Some("concrete".to_string()),
+                        });
+                    }
+                }
             }
         }
 
-        // Calculate max bar diameter for bending radius
-        let mut max_bar_diam = 0.0;
-        for entry in &props.rebar {
-            let d = parse_size(&entry.size);
-            if d > max_bar_diam {
-                max_bar_diam = d;
-            }
-        }
-        if max_bar_diam == 0.0 {
-            max_bar_diam = 0.95; // Fallback to #3 stirrup size if no rebar
-        }
-
-        // Draw Stirrups (Section View)
-        let cover = props.cover.unwrap_or(4.0);
-        let mut stirrup_size = 0.95; // Default #3
-        if let Some(ties) = &props.ties {
-            stirrup_size = parse_size(&ties.size);
-        }
+        let (cover, stirrup_size, insets, bend_radius) = tie_layout(props);
 
-        let inset = cover + stirrup_size / 2.0;
+        // Draw Rebar (Section View)
+        let bars = place_bars(section, props, (insets, bend_radius), warnings);
 
+        // Draw Stirrups (Section View), around the bars
         if let Some(shape) = &props.shape {
             match shape {
-                Shape::Rect { width, height } => {
-                    let w = width - 2.0 * inset;
-                    let h = height - 2.0 * inset;
-                    let x = -width / 2.0 + inset;
-                    let y = -height / 2.0 + inset;
-
-                    let inner_r = (2.0 * stirrup_size).max(max_bar_diam / 2.0);
-                    let r = inner_r + stirrup_size / 2.0;
-
-                    // Generate rounded rect path
-                    let mut points = Vec::new();
-
-                    // Start top-left (after corner)
-                    // Top edge
-                    points.push((x + r, y + h));
-                    points.push((x + w - r, y + h));
-
-                    // Top-right corner
-                    add_arc(&mut points, x + w - r, y + h - r, r, PI / 2.0, 0.0);
-
-                    // Right edge
-                    points.push((x + w, y + h - r));
-                    points.push((x + w, y + r));
-
-                    // Bottom-right corner
-                    add_arc(&mut points, x + w - r, y + r, r, 0.0, -PI / 2.0);
-
-                    // Bottom edge
-                    points.push((x + w - r, y));
-                    points.push((x + r, y));
-
-                    // Bottom-left corner
-                    add_arc(&mut points, x + r, y + r, r, -PI / 2.0, -PI);
-
-                    // Left edge
-                    points.push((x, y + r));
-                    points.push((x, y + h - r));
-
-                    // Top-left corner
-                    add_arc(&mut points, x + r, y + h - r, r, PI, PI / 2.0);
-
-                    // Add main stirrup path
+                Shape::Rect { .. } | Shape::Tee { .. } | Shape::Ell { .. } => {
+                    // The stirrup encloses the web over the full height
+                    let (left, right) = web_x_range(shape);
+                    let height = shape_height(shape);
+                    let center = stirrup_size / 2.0;
+                    let hoop = ties::Hoop {
+                        x: left + cover.sides + center,
+                        y: -height / 2.0 + cover.bottom + center,
+                        w: right - left - 2.0 * (cover.sides + center),
+                        h: height - cover.top - cover.bottom - stirrup_size,
+                        r: bend_radius + center,
+                        size: stirrup_size,
+                    };
+                    for tie in ties::draw(&mut section_drawing, &props.ties, &hoop, &bars) {
+                        warnings.push(warning(
+                            section,
+                            tie.line,
+                            Severity::Warning,
+                            "tie-without-bars",
+                            format!(
+                                "`{}` ties have no bars on opposite faces to pass through",
+                                tie.kind.keyword()
+                            ),
+                        ));
+                    }
+                }
+                Shape::Polygon { outer, voids } => {
+                    let (outline, _) = polygon::centered(outer, voids);
                     section_drawing.add(Primitive::Path {
-                        points: points.clone(),
+                        points: polygon::tie(&outline, insets, stirrup_size),
                         closed: true,
                         stroke: Some(Stroke {
                             color: "#000080".to_string(),
                             width: 2.0,
-                        }), // Navy
+                        }),
                         fill: None,
                         group: Some("stirrup".to_string()),
                     });
@@ -198,7 +243,7 @@ pub fn generate(section: &Section) -> Vec<Drawing> {
                     section_drawing.add(Primitive::Circle {
                         x: 0.0,
                         y: 0.0,
-                        radius: diameter / 2.0 - inset,
+                        radius: diameter / 2.0 - cover.sides - stirrup_size / 2.0,
                         stroke: Some(Stroke {
                             color: "#000080".to_string(),
                             width: 2.0,
@@ -208,231 +253,260 @@ pub fn generate(section: &Section) -> Vec<Drawing> {
                     });
                 }
             }
-        }
 
-        // Draw Rebar (Section View)
-        let base_inset = cover + stirrup_size;
-
-        let mut top_entries = Vec::new();
-        let mut bot_entries = Vec::new();
-        let mut other_entries = Vec::new();
-
-        for entry in &props.rebar {
-            match entry.pattern {
-                RebarPattern::Top => top_entries.push(entry),
-                RebarPattern::Bottom => bot_entries.push(entry),
-                _ => other_entries.push(entry),
+            // Spirals are only drawn in circular sections, which take no other
+            // ties; a polygon takes a closed tie along its outline
+            let circular = matches!(shape, Shape::Circle { .. });
+            let polygonal = matches!(shape, Shape::Polygon { .. });
+            for tie in &props.ties {
+                let drawn = match tie.kind {
+                    StirrupKind::Closed => true,
+                    StirrupKind::Spiral { .. } => circular,
+                    _ => !circular && !polygonal,
+                };
+                if !drawn {
+                    warnings.push(warning(
+                        section,
+                        tie.line,
+                        Severity::Warning,
+                        "tie-ignored",
+                        format!("`{}` ties are not drawn in this shape", tie.kind.keyword()),
+                    ));
+                }
             }
         }
 
-        for (i, entry) in top_entries.iter().enumerate() {
-            let bar_diam = parse_size(&entry.size);
-            let layer_offset = (i as f64) * (bar_diam + 2.5);
-            let vertical_inset = base_inset + bar_diam / 2.0 + layer_offset;
-            let horizontal_inset = base_inset + bar_diam / 2.0;
-            draw_linear_pattern(
-                &mut section_drawing,
-                props.shape.as_ref(),
-                entry,
-                vertical_inset,
-                horizontal_inset,
-                true,
-            );
+        if let Some(shape) = &props.shape {
+            checks::outside(warnings, section, &props.rebar, &bars, shape);
+        }
+        checks::overlaps(warnings, section, &props.rebar, &bars);
+        checks::clear_spacing(
+            warnings,
+            section,
+            &props.rebar,
+            &bars,
+            props.aggregate,
+            units,
+        );
+
+        for bar in &bars {
+            add_rebar_circle(&mut section_drawing, bar.x, bar.y, bar.diameter, &bar.size);
         }
 
-        for (i, entry) in bot_entries.iter().rev().enumerate() {
-            let bar_diam = parse_size(&entry.size);
-            let layer_offset = (i as f64) * (bar_diam + 2.5);
-            let vertical_inset = base_inset + bar_diam / 2.0 + layer_offset;
-            let horizontal_inset = base_inset + bar_diam / 2.0;
-            draw_linear_pattern(
+        if let Some(shape) = &props.shape
+            && let Some(mode @ (Dims::On | Dims::Full)) = &props.dims
+        {
+            annotations::section_dimensions(
                 &mut section_drawing,
-                props.shape.as_ref(),
-                entry,
-                vertical_inset,
-                horizontal_inset,
-                false,
+                shape,
+                mode,
+                &cover,
+                &bars,
+                units,
             );
         }
 
-        for entry in other_entries {
-            let bar_diam = parse_size(&entry.size);
-            let current_inset = base_inset + bar_diam / 2.0;
-            if let RebarPattern::Perimeter = entry.pattern {
-                if let Some(Shape::Circle { diameter }) = props.shape {
-                    let r = diameter / 2.0 - current_inset;
-                    let step_angle = 2.0 * PI / (entry.count as f64);
-
-                    for i in 0..entry.count {
-                        let angle = (i as f64) * step_angle;
-                        let x = r * angle.cos();
-                        let y = r * angle.sin();
-                        add_rebar_circle(&mut section_drawing, x, y, bar_diam, &entry.size);
-                    }
-                }
-            }
+        if let Some(shape) = &props.shape
+            && let Some(mode) = &props.labels
+            && *mode != Labels::Off
+        {
+            annotations::section_labels(
+                &mut section_drawing,
+                shape,
+                mode,
+                props.dims.as_ref(),
+                &props.rebar,
+                &bars,
+                units,
+            );
         }
 
         drawings.push(section_drawing);
     }
 
-    // --- Longitudinal Drawing ---
-    if show_longitudinal {
-        if let Some(span) = props.span {
-            let mut long_drawing = Drawing::new();
-            long_drawing.id = Some(format!("{} (Longitudinal)", section.id));
-            long_drawing.id = Some(format!("{} (Longitudinal)", section.id));
-            long_drawing.scale = props.scale_long;
-
-            let height = match &props.shape {
-                Some(Shape::Rect { height, .. }) => *height,
-                Some(Shape::Circle { diameter }) => *diameter,
-                None => 0.0,
-            };
+    if show_longitudinal && props.span.is_none() {
+        warnings.push(warning(
+            section,
+            section.line,
+            Severity::Warning,
+            "missing-span",
+            "the longitudinal view needs a `span`".to_string(),
+        ));
+    }
 
-            if height > 0.0 {
-                // Draw Concrete (Longitudinal)
-                long_drawing.add(Primitive::Rect {
-                    x: 0.0,
-                    y: -height / 2.0,
-                    width: span,
-                    height: height,
-                    stroke: Some(Stroke {
-                        color: "black".to_string(),
-                        width: 1.0,
-                    }),
-                    fill: None,
-                    group: Some("concrete".to_string()),
-                });
+    // --- Longitudinal Drawing ---
+    if show_longitudinal && let Some(span) = props.span {
+        let mut long_drawing = Drawing::new();
+        long_drawing.id = Some(format!("{} (Longitudinal)", section.id));
+        long_drawing.scale = props.scale_long;
+
+        let height = props.shape.as_ref().map_or(0.0, shape_height);
+
+        if height > 0.0 {
+            // Draw Concrete (Longitudinal)
+            long_drawing.add(Primitive::Rect {
+                x: 0.0,
+                y: -height / 2.0,
+                width: span,
+                height,
+                stroke: Some(Stroke {
+                    color: "black".to_string(),
+                    width: 1.0,
+                }),
+                fill: None,
+                group: Some("concrete".to_string()),
+            });
 
-                let cover = props.cover.unwrap_or(4.0);
+            let cover = props.cover.unwrap_or(Cover::uniform(4.0));
+            // Bars and ties stop the side cover short of the ends of the span
+            let end = cover.sides;
+
+            // Draw Hatched Ends (Supports)
+            let support_width = 20.0;
+            add_hatched_rect(
+                &mut long_drawing,
+                -support_width,
+                -height / 2.0,
+                support_width,
+                height,
+            );
+            add_hatched_rect(
+                &mut long_drawing,
+                span,
+                -height / 2.0,
+                support_width,
+                height,
+            );
 
-                // Draw Hatched Ends (Supports)
-                let support_width = 20.0;
-                add_hatched_rect(
-                    &mut long_drawing,
-                    -support_width,
-                    -height / 2.0,
-                    support_width,
-                    height,
-                );
-                add_hatched_rect(
+            if let Some(Dims::On | Dims::Full) = &props.dims {
+                annotations::longitudinal_dimensions(
                     &mut long_drawing,
                     span,
-                    -height / 2.0,
-                    support_width,
                     height,
+                    support_width,
+                    units,
                 );
+            }
 
-                let mut stirrup_size = 0.95; // Default #3
-                if let Some(ties) = &props.ties {
-                    stirrup_size = parse_size(&ties.size);
-                }
-                let base_inset = cover + stirrup_size;
-
-                // Draw Longitudinal Bars (Top)
-                for entry in &props.rebar {
-                    if let RebarPattern::Top = entry.pattern {
-                        let bar_diam = parse_size(&entry.size);
-                        let color = get_color_for_size(&entry.size);
-                        let y = height / 2.0 - base_inset - bar_diam / 2.0;
-
-                        // Draw as filled Rect
-                        long_drawing.add(Primitive::Rect {
-                            x: cover,
-                            y: y - bar_diam / 2.0,
-                            width: span - 2.0 * cover,
-                            height: bar_diam,
-                            stroke: None,
-                            fill: Some(color),
-                            group: Some("rebar_long".to_string()),
-                        });
-                    }
+            let stirrup_size = props.outer_tie().map_or(0.95, |t| t.bar.diameter); // Default #3
+            let insets = Insets::new(&cover, stirrup_size);
+
+            // Draw Longitudinal Bars (Top)
+            for (index, layer_offset) in layer_offsets(props, &RebarPattern::Top) {
+                let (bar_diam, size) = largest_bar(&props.rebar[index]);
+                let color = get_color_for_size(size);
+                let y = height / 2.0 - insets.top - layer_offset - bar_diam / 2.0;
+
+                // Draw as filled Rect
+                long_drawing.add(Primitive::Rect {
+                    x: end,
+                    y: y - bar_diam / 2.0,
+                    width: span - 2.0 * end,
+                    height: bar_diam,
+                    stroke: None,
+                    fill: Some(color),
+                    group: Some("rebar_long".to_string()),
+                });
+            }
+
+            // Draw Longitudinal Bars (Bottom)
+            for (index, layer_offset) in layer_offsets(props, &RebarPattern::Bottom) {
+                let (bar_diam, size) = largest_bar(&props.rebar[index]);
+                let color = get_color_for_size(size);
+                let y = -height / 2.0 + insets.bottom + layer_offset + bar_diam / 2.0;
+
+                // Draw as filled Rect
+                long_drawing.add(Primitive::Rect {
+                    x: end,
+                    y: y - bar_diam / 2.0,
+                    width: span - 2.0 * end,
+                    height: bar_diam,
+                    stroke: None,
+                    fill: Some(color),
+                    group: Some("rebar_long".to_string()),
+                });
+            }
+
+            // Draw Longitudinal Bars (Mid), which a polygon does not take
+            for entry in &props.rebar {
+                if let (RebarPattern::Mid, Some(shape)) = (&entry.pattern, &props.shape)
+                    && !matches!(shape, Shape::Polygon { .. })
+                {
+                    let (bar_diam, size) = largest_bar(entry);
+                    let color = get_color_for_size(size);
+                    let y = centroid_y(shape);
+
+                    long_drawing.add(Primitive::Rect {
+                        x: end,
+                        y: y - bar_diam / 2.0,
+                        width: span - 2.0 * end,
+                        height: bar_diam,
+                        stroke: None,
+                        fill: Some(color),
+                        group: Some("rebar_long".to_string()),
+                    });
                 }
+            }
 
-                // Draw Longitudinal Bars (Bottom)
-                for entry in &props.rebar {
-                    if let RebarPattern::Bottom = entry.pattern {
-                        let bar_diam = parse_size(&entry.size);
-                        let color = get_color_for_size(&entry.size);
-                        let y = -height / 2.0 + base_inset + bar_diam / 2.0;
-
-                        // Draw as filled Rect
-                        long_drawing.add(Primitive::Rect {
-                            x: cover,
-                            y: y - bar_diam / 2.0,
-                            width: span - 2.0 * cover,
-                            height: bar_diam,
-                            stroke: None,
-                            fill: Some(color),
-                            group: Some("rebar_long".to_string()),
-                        });
-                    }
+            // Draw Stirrups, every tie of a `ties:` block at its own spacing
+            for tie in &props.ties {
+                let stirrup_color = get_color_for_size(&tie.size);
+                if let (StirrupKind::Spiral { pitch }, Some(Shape::Circle { diameter })) =
+                    (tie.kind, &props.shape)
+                {
+                    let radius = diameter / 2.0 - cover.sides - tie.bar.diameter / 2.0;
+                    let ends = (end, span - end);
+                    let Some(spiral) = ties::spiral(ends, radius, pitch, tie.bar.diameter) else {
+                        warnings.push(warning(
+                            section,
+                            tie.line,
+                            Severity::Warning,
+                            "spiral-too-short",
+                            format!(
+                                "the span is too short for the spiral, whose closed turns at both ends take {}{}",
+                                annotations::format_length(
+                                    2.0 * ties::EXTRA_TURNS * tie.bar.diameter,
+                                    units
+                                ),
+                                units.suffix()
+                            ),
+                        ));
+                        continue;
+                    };
+                    long_drawing.add(Primitive::Path {
+                        points: spiral.points,
+                        closed: false,
+                        stroke: Some(Stroke {
+                            color: stirrup_color,
+                            width: 1.0,
+                        }),
+                        fill: None,
+                        group: Some("stirrup_long".to_string()),
+                    });
+                    annotations::spiral_quantities(
+                        &mut long_drawing,
+                        height,
+                        props.dims.as_ref(),
+                        &tie.size,
+                        (spiral.turns, spiral.length),
+                        units,
+                    );
+                    continue;
                 }
 
-                // Draw Stirrups
-                if let Some(ties) = &props.ties {
-                    let mut left_x = cover;
-                    let mut right_x = span - cover;
-                    let stirrup_color = get_color_for_size(&ties.size);
-
-                    for spacing in &ties.dist {
-                        match spacing {
-                            crate::parser::ast::Spacing::Fixed { count, dist } => {
-                                for _ in 0..*count {
-                                    left_x += dist;
-                                    if left_x >= right_x {
-                                        break;
-                                    }
-                                    add_stirrup_line(
-                                        &mut long_drawing,
-                                        left_x,
-                                        height,
-                                        cover,
-                                        stirrup_size,
-                                        &stirrup_color,
-                                    );
-
-                                    right_x -= dist;
-                                    if right_x <= left_x {
-                                        break;
-                                    }
-                                    add_stirrup_line(
-                                        &mut long_drawing,
-                                        right_x,
-                                        height,
-                                        cover,
-                                        stirrup_size,
-                                        &stirrup_color,
-                                    );
-                                }
-                            }
-                            crate::parser::ast::Spacing::Rest { dist } => {
-                                let gap = right_x - left_x;
-                                if gap > 0.0 {
-                                    let num_spaces = (gap / dist).ceil() as u32;
-                                    if num_spaces > 0 {
-                                        let actual_dist = gap / (num_spaces as f64);
-                                        for k in 1..num_spaces {
-                                            let pos = left_x + (k as f64) * actual_dist;
-                                            add_stirrup_line(
-                                                &mut long_drawing,
-                                                pos,
-                                                height,
-                                                cover,
-                                                stirrup_size,
-                                                &stirrup_color,
-                                            );
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
+                for x in ties::positions(&tie.dist, end, span - end) {
+                    add_stirrup_line(
+                        &mut long_drawing,
+                        x,
+                        height,
+                        &cover,
+                        tie.bar.diameter,
+                        &stirrup_color,
+                    );
                 }
             }
-            drawings.push(long_drawing);
         }
+        drawings.push(long_drawing);
     }
 
     drawings
@@ -442,12 +516,12 @@ fn add_stirrup_line(
     drawing: &mut Drawing,
     x: f64,
     height: f64,
-    cover: f64,
+    cover: &Cover,
     size: f64,
     color: &str,
 ) {
-    let y_top = height / 2.0 - cover;
-    let y_bot = -height / 2.0 + cover;
+    let y_top = height / 2.0 - cover.top;
+    let y_bot = -height / 2.0 + cover.bottom;
     let h = y_top - y_bot;
 
     drawing.add(Primitive::Rect {
@@ -477,40 +551,793 @@ fn add_arc(
     }
 }
 
+/// Places a layer of bars across the web, in declaration order. Each bar
+/// rests on the stirrup, so bars of different sizes keep the same face inset;
+/// the outer bars of the layer against the stirrup sit in its bends instead.
+/// A top layer that does not fit in the web of a flanged section spreads
+/// across the flange. With corner bars, the layer is spaced evenly `between`
+/// their centers. Lines given by spacing stay in the web.
 fn draw_linear_pattern(
-    drawing: &mut Drawing,
+    bars: &mut Vec<PlacedBar>,
     shape: Option<&Shape>,
-    entry: &crate::parser::ast::RebarEntry,
-    v_inset: f64,
-    h_inset: f64,
+    (index, entry): (usize, &RebarEntry),
+    (insets, bend_radius): (Insets, f64),
+    layer_offset: f64,
     is_top: bool,
+    between: Option<(f64, f64)>,
 ) {
-    let bar_diam = parse_size(&entry.size);
-    if let Some(Shape::Rect { width, height }) = shape {
-        let y = if is_top {
-            height / 2.0 - v_inset
-        } else {
-            -height / 2.0 + v_inset
-        };
-        let start_x = -width / 2.0 + h_inset;
-        let end_x = width / 2.0 - h_inset;
-        let step = if entry.count > 1 {
-            (end_x - start_x) / (entry.count as f64 - 1.0)
-        } else {
-            0.0
+    let Some(shape) = shape else { return };
+    if let Shape::Polygon { outer, voids } = shape {
+        let (outline, _) = polygon::centered(outer, voids);
+        polygon::draw_layer(bars, &outline, (index, entry), insets, layer_offset, is_top);
+        return;
+    }
+
+    let sizes: Vec<(usize, &BarGroup)> = entry.bars().collect();
+    let diams: Vec<f64> = sizes.iter().map(|(_, g)| g.bar.diameter).collect();
+
+    let web = web_x_range(shape);
+    let base_inset = insets.sides;
+    let inside = insets.inside(web, shape_height(shape));
+    let (face, inward) = if is_top {
+        (inside.3, -1.0)
+    } else {
+        (inside.2, 1.0)
+    };
+    // Heights of the first and last bars, when they sit in the bends
+    let mut seats = None;
+    let (sizes, xs) = match (entry.spacing, between) {
+        (Some(spacing), _) => spaced_layer(entry, spacing, web, base_inset, between),
+        (None, Some((from, to))) => (sizes, interior_positions(from, to, diams.len())),
+        (None, None) => {
+            if is_top
+                && let Some(flange) = flange_x_range(shape)
+                && !fits_in_layer(web, base_inset, &diams)
+            {
+                (sizes, layer_x_positions(flange, base_inset, &diams))
+            } else if layer_offset == 0.0 && diams.len() > 1 {
+                let corners = if is_top { (0, 1) } else { (3, 2) };
+                let first = corner_seat(inside, corners.0, bend_radius, diams[0]);
+                let last = corner_seat(inside, corners.1, bend_radius, diams[diams.len() - 1]);
+                seats = Some((first.1, last.1));
+
+                let (from, to) = (first.0, last.0);
+                let mut xs = vec![from];
+                xs.extend(interior_positions(from, to, diams.len() - 2));
+                xs.push(to);
+                (sizes, xs)
+            } else {
+                (sizes, layer_x_positions(web, base_inset, &diams))
+            }
+        }
+    };
+
+    let last = sizes.len().saturating_sub(1);
+    for (i, ((group, bar_group), x)) in sizes.into_iter().zip(xs).enumerate() {
+        let bar_diam = bar_group.bar.diameter;
+        let y = match seats {
+            Some((first, _)) if i == 0 => first,
+            Some((_, seat)) if i == last => seat,
+            _ => face + inward * (bar_diam / 2.0 + layer_offset),
         };
+        place_bar(bars, x, y, bar_diam, &bar_group.size, (index, group));
+    }
+}
+
+/// Bars of a line given by spacing and their centers across a layer. Between
+/// the stirrup legs of `range`, as many bars as fit at `spacing`, with the
+/// leftover shared by both ends; `between` corner bars, the fewest that keep
+/// every gap within `spacing`.
+fn spaced_layer(
+    entry: &RebarEntry,
+    spacing: f64,
+    (left, right): (f64, f64),
+    base_inset: f64,
+    between: Option<(f64, f64)>,
+) -> (Vec<(usize, &BarGroup)>, Vec<f64>) {
+    let group = &entry.groups[0];
+    let xs = match between {
+        Some((from, to)) => {
+            let gaps = ((to - from) / spacing - 1e-9).ceil().max(1.0);
+            interior_positions(from, to, gaps as usize - 1)
+        }
+        None => {
+            let from = left + base_inset + group.bar.diameter / 2.0;
+            let to = right - base_inset - group.bar.diameter / 2.0;
+            // A width that is an exact multiple of the spacing keeps its last bar
+            let count = ((to - from) / spacing + 1e-9).floor().max(0.0) as usize + 1;
+            let start = (from + to - (count - 1) as f64 * spacing) / 2.0;
+            (0..count).map(|i| start + i as f64 * spacing).collect()
+        }
+    };
+    (vec![(0, group); xs.len()], xs)
+}
+
+/// Diameter of the largest bar seated in a stirrup bend: a `corners` line,
+/// or else the corner bars of a `perim` line and the end bars of the
+/// outermost layers that `draw_linear_pattern` places in the bends.
+fn seated_diameter(props: &SectionProperties, shape: &Shape, base_inset: f64) -> f64 {
+    if let Some(entry) = props
+        .rebar
+        .iter()
+        .find(|e| e.pattern == RebarPattern::Corners)
+    {
+        return largest_bar(entry).0;
+    }
+
+    let perimeter = props
+        .rebar
+        .iter()
+        .filter(|e| e.pattern == RebarPattern::Perimeter)
+        .flat_map(|e| e.bars().take(4).map(|(_, g)| g.bar.diameter));
+
+    let layers = [(RebarPattern::Top, true), (RebarPattern::Bottom, false)]
+        .into_iter()
+        .filter_map(|(pattern, is_top)| {
+            let &(index, _) = layer_offsets(props, &pattern).first()?;
+            let entry = &props.rebar[index];
+            let diams: Vec<f64> = entry.bars().map(|(_, g)| g.bar.diameter).collect();
+            let spread = is_top
+                && flange_x_range(shape).is_some()
+                && !fits_in_layer(web_x_range(shape), base_inset, &diams);
+            (entry.spacing.is_none() && diams.len() > 1 && !spread)
+                .then(|| diams[0].max(diams[diams.len() - 1]))
+        });
+
+    perimeter.chain(layers).fold(0.0, f64::max)
+}
 
-        for i in 0..entry.count {
-            let x = if entry.count > 1 {
+/// Whether the bars keep a clear spacing of at least one diameter (and
+/// 2.5 cm) between the stirrup legs of the given range.
+fn fits_in_layer((left, right): (f64, f64), base_inset: f64, diams: &[f64]) -> bool {
+    if diams.len() < 2 {
+        return true;
+    }
+    let free = right - left - 2.0 * base_inset - diams.iter().sum::<f64>();
+    let clear = free / (diams.len() as f64 - 1.0);
+    clear >= diams.iter().copied().fold(2.5, f64::max)
+}
+
+/// Places a `mid` line across the width at the height of the section centroid.
+fn draw_mid_pattern(
+    bars: &mut Vec<PlacedBar>,
+    shape: &Shape,
+    index: usize,
+    entry: &RebarEntry,
+    base_inset: f64,
+) {
+    let y = centroid_y(shape);
+    let range = match flange_x_range(shape) {
+        Some(flange) if y >= shape_height(shape) / 2.0 - flange_thickness(shape) => flange,
+        _ => web_x_range(shape),
+    };
+
+    let (sizes, xs) = match entry.spacing {
+        Some(spacing) => spaced_layer(entry, spacing, range, base_inset, None),
+        None => {
+            let sizes: Vec<(usize, &BarGroup)> = entry.bars().collect();
+            let diams: Vec<f64> = sizes.iter().map(|(_, g)| g.bar.diameter).collect();
+            let xs = layer_x_positions(range, base_inset, &diams);
+            (sizes, xs)
+        }
+    };
+
+    for ((group, bar_group), x) in sizes.into_iter().zip(xs) {
+        let bar_diam = bar_group.bar.diameter;
+        place_bar(bars, x, y, bar_diam, &bar_group.size, (index, group));
+    }
+}
+
+/// Bar centers spread evenly across a layer, the outer bars touching the
+/// stirrup. A single bar is centered.
+fn layer_x_positions((left, right): (f64, f64), base_inset: f64, diams: &[f64]) -> Vec<f64> {
+    let count = diams.len();
+    let start_x = left + base_inset + diams.first().unwrap_or(&0.0) / 2.0;
+    let end_x = right - base_inset - diams.last().unwrap_or(&0.0) / 2.0;
+    let step = if count > 1 {
+        (end_x - start_x) / (count as f64 - 1.0)
+    } else {
+        0.0
+    };
+
+    (0..count)
+        .map(|i| {
+            if count > 1 {
                 start_x + (i as f64) * step
             } else {
-                0.0
+                (left + right) / 2.0
+            }
+        })
+        .collect()
+}
+
+/// `count` points spaced evenly between `from` and `to`, excluding both.
+fn interior_positions(from: f64, to: f64, count: usize) -> Vec<f64> {
+    let step = (to - from) / (count as f64 + 1.0);
+    (1..=count).map(|i| from + i as f64 * step).collect()
+}
+
+/// Places a `corners` line in the stirrup bends of the web: top-left,
+/// top-right, bottom-right and bottom-left. Each bar touches the inside of
+/// its bend on the diagonal. Returns the centers of the bars.
+fn draw_corners(
+    bars: &mut Vec<PlacedBar>,
+    (index, entry): (usize, &RebarEntry),
+    web: (f64, f64),
+    height: f64,
+    (insets, bend_radius): (Insets, f64),
+) -> [(f64, f64); 4] {
+    let inside = insets.inside(web, height);
+    let mut seats = [(0.0, 0.0); 4];
+
+    for (corner, (seat, (group, bar_group))) in seats.iter_mut().zip(entry.bars()).enumerate() {
+        let bar_diam = bar_group.bar.diameter;
+        *seat = corner_seat(inside, corner, bend_radius, bar_diam);
+        place_bar(
+            bars,
+            seat.0,
+            seat.1,
+            bar_diam,
+            &bar_group.size,
+            (index, group),
+        );
+    }
+    seats
+}
+
+/// Center of a bar that touches the inside of a stirrup bend on the
+/// diagonal, given the `inside` faces of the stirrup and the index of the
+/// corner: top-left, top-right, bottom-right or bottom-left.
+fn corner_seat(
+    (left, right, bottom, top): (f64, f64, f64, f64),
+    corner: usize,
+    bend_radius: f64,
+    bar_diam: f64,
+) -> (f64, f64) {
+    // From the center of the bend towards the corner of the stirrup
+    let along = (bend_radius - bar_diam / 2.0) / 2.0_f64.sqrt();
+    let inset = bend_radius - along;
+    match corner {
+        0 => (left + inset, top - inset),
+        1 => (right - inset, top - inset),
+        2 => (right - inset, bottom + inset),
+        _ => (left + inset, bottom + inset),
+    }
+}
+
+/// Cover, diameter of the outer tie, insets of the bars from the faces and
+/// inside radius of the tie bends, around the largest bar seated in them if
+/// the mandrel is smaller.
+fn tie_layout(props: &SectionProperties) -> (Cover, f64, Insets, f64) {
+    let cover = props.cover.unwrap_or(Cover::uniform(4.0));
+    let stirrup_size = props.outer_tie().map_or(0.95, |t| t.bar.diameter); // Default #3
+    let insets = Insets::new(&cover, stirrup_size);
+    let seated_diam = props
+        .shape
+        .as_ref()
+        .map_or(0.0, |shape| seated_diameter(props, shape, insets.sides));
+    let bend_radius = (tie_bend_diameter(stirrup_size) / 2.0).max(seated_diam / 2.0);
+    (cover, stirrup_size, insets, bend_radius)
+}
+
+/// Bars of every rebar line in the section view. Lines the shape does not
+/// take are reported in `warnings`.
+fn place_bars(
+    section: &Section,
+    props: &SectionProperties,
+    (insets, bend_radius): (Insets, f64),
+    warnings: &mut Vec<Warning>,
+) -> Vec<PlacedBar> {
+    let base_inset = insets.sides;
+    let mut bars = Vec::new();
+
+    // Bars seated in the stirrup bends; the other lines on the faces are
+    // spread between them
+    let corners = props
+        .rebar
+        .iter()
+        .enumerate()
+        .find(|(_, e)| e.pattern == RebarPattern::Corners)
+        .zip(props.shape.as_ref())
+        .filter(|(_, shape)| !matches!(shape, Shape::Circle { .. } | Shape::Polygon { .. }))
+        .map(|((index, entry), shape)| {
+            draw_corners(
+                &mut bars,
+                (index, entry),
+                web_x_range(shape),
+                shape_height(shape),
+                (insets, bend_radius),
+            )
+        });
+
+    let top_layers = layer_offsets(props, &RebarPattern::Top);
+    let bot_layers = layer_offsets(props, &RebarPattern::Bottom);
+    for (layers, is_top) in [(&top_layers, true), (&bot_layers, false)] {
+        for (i, &(index, layer_offset)) in layers.iter().enumerate() {
+            let between = match corners {
+                Some([top_left, top_right, ..]) if i == 0 && is_top => {
+                    Some((top_left.0, top_right.0))
+                }
+                Some([.., bottom_right, bottom_left]) if i == 0 => {
+                    Some((bottom_left.0, bottom_right.0))
+                }
+                _ => None,
+            };
+            draw_linear_pattern(
+                &mut bars,
+                props.shape.as_ref(),
+                (index, &props.rebar[index]),
+                (insets, bend_radius),
+                layer_offset,
+                is_top,
+                between,
+            );
+        }
+    }
+
+    // Side bars fill the height between the outermost top and bottom layers
+    let top_layer = top_layers
+        .first()
+        .map(|&(i, _)| largest_bar(&props.rebar[i]).0);
+    let bot_layer = bot_layers
+        .first()
+        .map(|&(i, _)| largest_bar(&props.rebar[i]).0);
+
+    // A circular section has no layers, so its `top` and `bot` lines are
+    // reported with the rest
+    let circular = matches!(props.shape, Some(Shape::Circle { .. }));
+    let other_entries = props
+        .rebar
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| match e.pattern {
+            RebarPattern::Top | RebarPattern::Bottom => circular,
+            RebarPattern::Corners => corners.is_none(),
+            _ => true,
+        });
+    for (index, entry) in other_entries {
+        match (&entry.pattern, props.shape.as_ref()) {
+            (RebarPattern::Perimeter, Some(Shape::Circle { diameter })) => {
+                let step_angle = 2.0 * PI / (entry.count() as f64);
+
+                for (i, (group, bar_group)) in entry.bars().enumerate() {
+                    let bar_diam = bar_group.bar.diameter;
+                    let r = diameter / 2.0 - base_inset - bar_diam / 2.0;
+                    let angle = (i as f64) * step_angle;
+                    let x = r * angle.cos();
+                    let y = r * angle.sin();
+                    place_bar(&mut bars, x, y, bar_diam, &bar_group.size, (index, group));
+                }
+            }
+            (RebarPattern::Perimeter, Some(Shape::Polygon { outer, voids })) => {
+                let (outline, _) = polygon::centered(outer, voids);
+                polygon::draw_perimeter(&mut bars, &outline, (index, entry), insets);
+            }
+            (RebarPattern::Perimeter, Some(shape)) => {
+                draw_rect_perimeter(
+                    &mut bars,
+                    index,
+                    entry,
+                    web_x_range(shape),
+                    shape_height(shape),
+                    (insets, bend_radius),
+                    corners.as_ref(),
+                );
+            }
+            (RebarPattern::Mid, Some(shape)) if !matches!(shape, Shape::Polygon { .. }) => {
+                draw_mid_pattern(&mut bars, shape, index, entry, base_inset);
+            }
+            (RebarPattern::Bar { x, y }, Some(shape)) => {
+                let (origin_x, origin_y) = match props.origin.unwrap_or_default() {
+                    Origin::Centroid => (centroid_x(shape), centroid_y(shape)),
+                    Origin::BottomLeft => (-shape_width(shape) / 2.0, -shape_height(shape) / 2.0),
+                };
+                let group = &entry.groups[0];
+                place_bar(
+                    &mut bars,
+                    origin_x + x,
+                    origin_y + y,
+                    group.bar.diameter,
+                    &group.size,
+                    (index, 0),
+                );
+            }
+            (RebarPattern::Sides, Some(shape))
+                if !matches!(shape, Shape::Circle { .. } | Shape::Polygon { .. }) =>
+            {
+                let height = shape_height(shape);
+                let bar_diam = largest_bar(entry).0;
+                let (y_top, y_bot) = match corners {
+                    Some([top_left, _, _, bottom_left]) => (top_left.1, bottom_left.1),
+                    None => (
+                        height / 2.0 - insets.top - top_layer.unwrap_or(bar_diam) / 2.0,
+                        -height / 2.0 + insets.bottom + bot_layer.unwrap_or(bar_diam) / 2.0,
+                    ),
+                };
+                draw_side_bars(
+                    &mut bars,
+                    index,
+                    entry,
+                    web_x_range(shape),
+                    y_top,
+                    y_bot,
+                    base_inset,
+                );
+            }
+            (pattern, _) => warnings.push(warning(
+                section,
+                entry.line,
+                Severity::Warning,
+                "pattern-ignored",
+                format!("`{}` bars are not drawn in this shape", pattern.keyword()),
+            )),
+        }
+    }
+    bars
+}
+
+/// Number of bars drawn for each rebar line of a section, including the
+/// lines given by spacing, which only know it once the section is laid out.
+pub fn bar_counts(section: &Section, defaults: &Defaults) -> Vec<u32> {
+    let props = &section.properties.with_defaults(defaults);
+    let (_, _, insets, bend_radius) = tie_layout(props);
+    let bars = place_bars(section, props, (insets, bend_radius), &mut Vec::new());
+    (0..props.rebar.len())
+        .map(|index| bars.iter().filter(|b| b.entry == index).count() as u32)
+        .collect()
+}
+
+/// Distance from each face of the section to the inside of the outer tie.
+#[derive(Debug, Clone, Copy)]
+struct Insets {
+    top: f64,
+    bottom: f64,
+    sides: f64,
+}
+
+impl Insets {
+    fn new(cover: &Cover, stirrup_size: f64) -> Self {
+        Insets {
+            top: cover.top + stirrup_size,
+            bottom: cover.bottom + stirrup_size,
+            sides: cover.sides + stirrup_size,
+        }
+    }
+
+    /// Left, right, bottom and top inside faces of the outer tie around a web.
+    fn inside(&self, (left, right): (f64, f64), height: f64) -> (f64, f64, f64, f64) {
+        (
+            left + self.sides,
+            right - self.sides,
+            -height / 2.0 + self.bottom,
+            height / 2.0 - self.top,
+        )
+    }
+}
+
+/// Horizontal position of the centroid relative to the middle of the bounding
+/// box; an L section, with its web on the left, and a polygon are not
+/// symmetric.
+fn centroid_x(shape: &Shape) -> f64 {
+    match shape {
+        Shape::Polygon { outer, voids } => polygon::centroid(outer, voids).0,
+        Shape::Ell {
+            width,
+            height,
+            flange_thickness,
+            web_thickness,
+        } => {
+            let flange_area = width * flange_thickness;
+            let web_area = web_thickness * (height - flange_thickness);
+            let web_x = -width / 2.0 + web_thickness / 2.0;
+            web_area * web_x / (flange_area + web_area)
+        }
+        _ => 0.0,
+    }
+}
+
+/// Height of the centroid relative to the middle of the bounding box.
+fn centroid_y(shape: &Shape) -> f64 {
+    match shape {
+        Shape::Rect { .. } | Shape::Circle { .. } => 0.0,
+        Shape::Polygon { outer, voids } => polygon::centroid(outer, voids).1,
+        Shape::Tee {
+            width,
+            height,
+            flange_thickness,
+            web_thickness,
+        }
+        | Shape::Ell {
+            width,
+            height,
+            flange_thickness,
+            web_thickness,
+        } => {
+            let flange_area = width * flange_thickness;
+            let web_area = web_thickness * (height - flange_thickness);
+            let flange_y = height / 2.0 - flange_thickness / 2.0;
+            let web_y = -height / 2.0 + (height - flange_thickness) / 2.0;
+            (flange_area * flange_y + web_area * web_y) / (flange_area + web_area)
+        }
+    }
+}
+
+/// Overall width of the section.
+fn shape_width(shape: &Shape) -> f64 {
+    match shape {
+        Shape::Rect { width, .. } | Shape::Tee { width, .. } | Shape::Ell { width, .. } => *width,
+        Shape::Circle { diameter } => *diameter,
+        Shape::Polygon { outer, .. } => {
+            let (left, right, _, _) = polygon::bounds(outer);
+            right - left
+        }
+    }
+}
+
+/// Overall height of the section.
+fn shape_height(shape: &Shape) -> f64 {
+    match shape {
+        Shape::Rect { height, .. } | Shape::Tee { height, .. } | Shape::Ell { height, .. } => {
+            *height
+        }
+        Shape::Circle { diameter } => *diameter,
+        Shape::Polygon { outer, .. } => {
+            let (_, _, bottom, top) = polygon::bounds(outer);
+            top - bottom
+        }
+    }
+}
+
+/// Horizontal extent of the web, which the stirrups enclose; the whole
+/// bounding box of a polygon.
+fn web_x_range(shape: &Shape) -> (f64, f64) {
+    match shape {
+        Shape::Rect { .. } | Shape::Polygon { .. } => {
+            let width = shape_width(shape);
+            (-width / 2.0, width / 2.0)
+        }
+        Shape::Circle { diameter } => (-diameter / 2.0, diameter / 2.0),
+        Shape::Tee { web_thickness, .. } => (-web_thickness / 2.0, web_thickness / 2.0),
+        Shape::Ell {
+            width,
+            web_thickness,
+            ..
+        } => (-width / 2.0, -width / 2.0 + web_thickness),
+    }
+}
+
+/// Horizontal extent of the top flange, for flanged sections.
+fn flange_x_range(shape: &Shape) -> Option<(f64, f64)> {
+    match shape {
+        Shape::Tee { width, .. } | Shape::Ell { width, .. } => Some((-width / 2.0, width / 2.0)),
+        Shape::Rect { .. } | Shape::Circle { .. } | Shape::Polygon { .. } => None,
+    }
+}
+
+fn flange_thickness(shape: &Shape) -> f64 {
+    match shape {
+        Shape::Tee {
+            flange_thickness, ..
+        }
+        | Shape::Ell {
+            flange_thickness, ..
+        } => *flange_thickness,
+        Shape::Rect { .. } | Shape::Circle { .. } | Shape::Polygon { .. } => 0.0,
+    }
+}
+
+/// Vertices of a flanged section, counter-clockwise from the bottom of the
+/// web. The flange is on top; an L has its web on the left.
+fn outline(shape: &Shape) -> Vec<(f64, f64)> {
+    let (left, right) = web_x_range(shape);
+    let top = shape_height(shape) / 2.0;
+    let under_flange = top - flange_thickness(shape);
+
+    match flange_x_range(shape) {
+        Some((flange_left, flange_right)) => {
+            let mut points = vec![
+                (left, -top),
+                (right, -top),
+                (right, under_flange),
+                (flange_right, under_flange),
+                (flange_right, top),
+                (flange_left, top),
+            ];
+            if left > flange_left {
+                points.push((flange_left, under_flange));
+                points.push((left, under_flange));
+            }
+            points
+        }
+        None => vec![(left, -top), (right, -top), (right, top), (left, top)],
+    }
+}
+
+/// Whether a bar lies within the outline of the concrete, clear of any void.
+fn in_concrete(shape: &Shape, bar: &PlacedBar) -> bool {
+    let (center, radius) = ((bar.x, bar.y), bar.diameter / 2.0);
+    match shape {
+        Shape::Circle { diameter } => bar.x.hypot(bar.y) + radius <= diameter / 2.0 + 1e-9,
+        Shape::Polygon { outer, voids } => {
+            let (outer, voids) = polygon::centered(outer, voids);
+            polygon::encloses(&outer, center, radius)
+                && voids.iter().all(|v| polygon::clear_of(v, center, radius))
+        }
+        _ => polygon::encloses(&outline(shape), center, radius),
+    }
+}
+
+/// Places the bars of a `sides` line on both vertical faces, evenly spaced
+/// between the top and bottom layers, top to bottom in declaration order.
+fn draw_side_bars(
+    bars: &mut Vec<PlacedBar>,
+    index: usize,
+    entry: &RebarEntry,
+    (left, right): (f64, f64),
+    y_top: f64,
+    y_bot: f64,
+    base_inset: f64,
+) {
+    let count = entry.count();
+    let step = (y_top - y_bot) / (count as f64 + 1.0);
+
+    for (i, (group, bar_group)) in entry.bars().enumerate() {
+        let bar_diam = bar_group.bar.diameter;
+        let offset = base_inset + bar_diam / 2.0;
+        let y = y_top - (i as f64 + 1.0) * step;
+        place_bar(
+            bars,
+            left + offset,
+            y,
+            bar_diam,
+            &bar_group.size,
+            (index, group),
+        );
+        place_bar(
+            bars,
+            right - offset,
+            y,
+            bar_diam,
+            &bar_group.size,
+            (index, group),
+        );
+    }
+}
+
+/// Places a `perim` line on a rectangle: the first four bars go to the
+/// corners, the rest are shared between the faces in proportion to their
+/// length and spaced evenly between the corners. The corner bars sit in the
+/// stirrup bends; when a `corners` line is already `seated`, every bar goes
+/// to the faces.
+fn draw_rect_perimeter(
+    bars: &mut Vec<PlacedBar>,
+    index: usize,
+    entry: &RebarEntry,
+    web: (f64, f64),
+    height: f64,
+    (insets, bend_radius): (Insets, f64),
+    seated: Option<&[(f64, f64); 4]>,
+) {
+    let inside = insets.inside(web, height);
+    let (left, right, bottom, top) = inside;
+    let sizes: Vec<(usize, &BarGroup)> = entry.bars().collect();
+    let corner_count = if seated.is_some() { 0 } else { 4 };
+    let (corners, faces) = sizes.split_at(sizes.len().min(corner_count));
+
+    // Corner bars: top-left, top-right, bottom-right, bottom-left
+    for (corner, &(group, bar_group)) in corners.iter().enumerate() {
+        let bar_diam = bar_group.bar.diameter;
+        let (x, y) = corner_seat(inside, corner, bend_radius, bar_diam);
+        place_bar(bars, x, y, bar_diam, &bar_group.size, (index, group));
+    }
+
+    // Corner bar centers the faces are spread between
+    let corner_diam = corners
+        .iter()
+        .map(|(_, g)| g.bar.diameter)
+        .fold(0.0, f64::max);
+    let (top_left, bottom_right) = match seated {
+        Some([top_left, _, bottom_right, _]) => (*top_left, *bottom_right),
+        None => (
+            corner_seat(inside, 0, bend_radius, corner_diam),
+            corner_seat(inside, 2, bend_radius, corner_diam),
+        ),
+    };
+    let (span_x, span_y) = (bottom_right.0 - top_left.0, top_left.1 - bottom_right.1);
+
+    let remaining = faces.len();
+    let per_horizontal = ((remaining as f64) * span_x / (2.0 * (span_x + span_y))).round() as usize;
+    let per_horizontal = per_horizontal.min(remaining / 2);
+    let per_vertical = (remaining - 2 * per_horizontal) / 2;
+    let extra_top = remaining - 2 * per_horizontal - 2 * per_vertical;
+
+    // Faces in order: top, bottom, left, right
+    let face_counts = [
+        per_horizontal + extra_top,
+        per_horizontal,
+        per_vertical,
+        per_vertical,
+    ];
+
+    let mut face_bars = faces.iter();
+    for (face, n) in face_counts.into_iter().enumerate() {
+        for k in 1..=n {
+            let Some(&(group, bar_group)) = face_bars.next() else {
+                return;
+            };
+            let bar_diam = bar_group.bar.diameter;
+            let t = k as f64 / (n as f64 + 1.0);
+            let along_x = top_left.0 + t * span_x;
+            let along_y = top_left.1 - t * span_y;
+            let (x, y) = match face {
+                0 => (along_x, top - bar_diam / 2.0),
+                1 => (along_x, bottom + bar_diam / 2.0),
+                2 => (left + bar_diam / 2.0, along_y),
+                _ => (right - bar_diam / 2.0, along_y),
             };
-            add_rebar_circle(drawing, x, y, bar_diam, &entry.size);
+            place_bar(bars, x, y, bar_diam, &bar_group.size, (index, group));
         }
     }
 }
 
+/// Index and distance from the stirrup of every layer of `pattern`, outermost
+/// first. Each layer sits the largest bar of the previous one plus the layer
+/// spacing further in. A circular section has none.
+fn layer_offsets(props: &SectionProperties, pattern: &RebarPattern) -> Vec<(usize, f64)> {
+    if let Some(Shape::Circle { .. }) = props.shape {
+        return Vec::new();
+    }
+    let spacing = props.layer_spacing.unwrap_or(LAYER_SPACING);
+    let mut offset = 0.0;
+    props
+        .layers(pattern)
+        .into_iter()
+        .map(|(_, index)| {
+            let layer = (index, offset);
+            offset += largest_bar(&props.rebar[index]).0 + spacing;
+            layer
+        })
+        .collect()
+}
+
+/// Diameter and size of the largest bar in a rebar line.
+fn largest_bar(entry: &RebarEntry) -> (f64, &str) {
+    entry
+        .groups
+        .iter()
+        .map(|g| (g.bar.diameter, g.size.as_str()))
+        .fold((0.0, ""), |max, bar| if bar.0 > max.0 { bar } else { max })
+}
+
+/// A longitudinal bar positioned in the section view.
+#[derive(Debug, Clone)]
+struct PlacedBar {
+    x: f64,
+    y: f64,
+    diameter: f64,
+    size: String,
+    entry: usize, // Index of the rebar line in the section
+    group: usize, // Index of the bar group in that line
+}
+
+fn place_bar(
+    bars: &mut Vec<PlacedBar>,
+    x: f64,
+    y: f64,
+    diameter: f64,
+    size: &str,
+    (entry, group): (usize, usize),
+) {
+    bars.push(PlacedBar {
+        x,
+        y,
+        diameter,
+        size: size.to_string(),
+        entry,
+        group,
+    });
+}
+
 fn add_rebar_circle(drawing: &mut Drawing, x: f64, y: f64, diam: f64, size_str: &str) {
     let color = get_color_for_size(size_str);
 
@@ -552,29 +1379,6 @@ fn get_color_for_size(size: &str) -> String {
     }
 }
 
-fn parse_size(size_str: &str) -> f64 {
-    if size_str.starts_with("#") {
-        if let Ok(num) = size_str[1..].parse::<f64>() {
-            return num * 0.3175; // 1/8 inch in cm
-        }
-    } else if size_str.ends_with("\"") {
-        let content = size_str.trim_end_matches("\"");
-        if content.contains('/') {
-            let parts: Vec<&str> = content.split('/').collect();
-            if parts.len() == 2 {
-                if let (Ok(num), Ok(den)) = (parts[0].parse::<f64>(), parts[1].parse::<f64>()) {
-                    return (num / den) * 2.54;
-                }
-            }
-        } else {
-            if let Ok(num) = content.parse::<f64>() {
-                return num * 2.54;
-            }
-        }
-    }
-    1.27 // Default
-}
-
 fn add_hatched_rect(drawing: &mut Drawing, x: f64, y: f64, width: f64, height: f64) {
     // Draw Rect Outline
     drawing.add(Primitive::Rect {
@@ -595,7 +1399,6 @@ fn add_hatched_rect(drawing: &mut Drawing, x: f64, y: f64, width: f64, height: f
     let num_lines = ((width + height) / spacing) as i32;
 
     for i in 0..num_lines {
-        let offset = (i as f64) * spacing;
         // Line equation: Y = X - offset (relative to rect origin)
         // We want to clip this line to the rectangle [x, x+width] x [y, y+height]
         // In local coords (0,0) to (width, height):
@@ -618,12 +1421,7 @@ fn add_hatched_rect(drawing: &mut Drawing, x: f64, y: f64, width: f64, height: f
         // User said "achurados" which means hatched.
         // Let's implement a simple hatch.
 
-        let x1 = x + offset;
-        let y1 = y;
-        let x2 = x + offset - height; // 45 degrees backwards
-        let y2 = y + height;
-
-        // We have a line segment from (x1, y1) to (x2, y2).
+        // We have a line segment from (x + offset, y) to (x + offset - height, y + height).
         // We need to clip it to the rectangle [x, x+width] x [y, y+height].
         // y range is already [y, y+height].
         // We just need to clip x range to [x, x+width].
@@ -674,3 +1472,1064 @@ fn add_hatched_rect(drawing: &mut Drawing, x: f64, y: f64, width: f64, height: f
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::catalog::Catalog;
+    use crate::parser;
+    use crate::parser::ast::Unit;
+
+    fn diameter(size: &str) -> f64 {
+        Catalog::Imperial.find(size).unwrap().diameter
+    }
+
+    /// Distance from the middle of a web to a bar of diameter `d` seated in
+    /// the bend of a #3 stirrup with 4cm of cover, along one axis.
+    fn seated(half: f64, d: f64) -> f64 {
+        let bend_radius = 2.0 * diameter("#3");
+        half - 4.0 - diameter("#3") - bend_radius + (bend_radius - d / 2.0) / 2.0_f64.sqrt()
+    }
+
+    fn warnings(input: &str) -> Vec<Warning> {
+        let document = parser::parse(input).unwrap();
+        let mut warnings = Vec::new();
+        generate(&document.sections[0], &document.defaults, &mut warnings);
+        warnings
+    }
+
+    fn section_drawing(input: &str) -> Drawing {
+        let document = parser::parse(input).unwrap();
+        generate(&document.sections[0], &document.defaults, &mut Vec::new()).remove(0)
+    }
+
+    /// Centers of the bars drawn in a section, from the outline circles.
+    fn bars(drawing: &Drawing) -> Vec<(f64, f64, f64)> {
+        drawing
+            .primitives
+            .iter()
+            .filter_map(|p| match p {
+                Primitive::Circle {
+                    x,
+                    y,
+                    radius,
+                    group: Some(g),
+                    ..
+                } if g == "rebar_outline" => Some((*x, *y, radius * 2.0)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_side_bars_between_layers() {
+        let drawing = section_drawing(
+            r#"
+            column "C-Rect":
+                40 x 40
+                cover 4
+                top 3 3/4"
+                bot 3 3/4"
+                sides 2 1/2"
+                ties 3/8" rto@20
+        "#,
+        );
+        let bars = bars(&drawing);
+        assert_eq!(bars.len(), 10);
+
+        let sides: Vec<_> = bars.iter().filter(|b| (b.2 - 1.27).abs() < 1e-9).collect();
+        assert_eq!(sides.len(), 4);
+        let x = 20.0 - 4.0 - 0.9525 - 0.635;
+        assert!(sides.iter().all(|b| (b.0.abs() - x).abs() < 1e-9));
+
+        // Three equal gaps between the top layer, the side bars and the bottom layer
+        let y_top = 20.0 - 4.0 - 0.9525 - 1.905 / 2.0;
+        let mut ys: Vec<f64> = sides.iter().map(|b| b.1).collect();
+        ys.sort_by(f64::total_cmp);
+        assert!((ys[3] - y_top / 3.0).abs() < 1e-9);
+        assert!((ys[0] + y_top / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_mid_bars_at_centroid() {
+        let input = r#"
+            beam "V-1":
+                30 x 90
+                cover 4
+                top 2 1"
+                mid 2 1/2"
+                bot 3 1"
+                span 400
+                view both
+        "#;
+        let document = parser::parse(input).unwrap();
+        let drawings = generate(&document.sections[0], &document.defaults, &mut Vec::new());
+
+        let mid: Vec<_> = bars(&drawings[0])
+            .into_iter()
+            .filter(|b| (b.2 - 1.27).abs() < 1e-9)
+            .collect();
+        assert_eq!(mid.len(), 2);
+        assert!(mid.iter().all(|b| b.1 == 0.0));
+        assert!((mid[0].0 + mid[1].0).abs() < 1e-9);
+
+        let long_bars = drawings[1]
+            .primitives
+            .iter()
+            .filter(|p| matches!(p, Primitive::Rect { group: Some(g), .. } if g == "rebar_long"))
+            .count();
+        assert_eq!(long_bars, 3);
+    }
+
+    #[test]
+    fn test_tee_section() {
+        let drawing = section_drawing(
+            r#"
+            beam "VT-1":
+                T 60 60 20 30
+                cover 4
+                top 2 1"
+                bot 3 1"
+                ties 3/8" rto@20
+        "#,
+        );
+
+        let outline = drawing.primitives.iter().find_map(|p| match p {
+            Primitive::Path {
+                points,
+                group: Some(g),
+                ..
+            } if g == "concrete" => Some(points.clone()),
+            _ => None,
+        });
+        assert_eq!(outline.map(|p| p.len()), Some(8));
+
+        // The stirrup stays inside the web
+        let stirrup = drawing.primitives.iter().find_map(|p| match p {
+            Primitive::Path {
+                points,
+                group: Some(g),
+                ..
+            } if g == "stirrup" => Some(points.clone()),
+            _ => None,
+        });
+        let max_x = stirrup
+            .unwrap()
+            .iter()
+            .map(|p| p.0.abs())
+            .fold(0.0, f64::max);
+        assert!((max_x - (15.0 - 4.0 - 0.9525 / 2.0)).abs() < 1e-9);
+
+        // Two top bars fit in the web corners
+        let bars = bars(&drawing);
+        assert!(bars.iter().all(|b| b.0.abs() < 15.0));
+    }
+
+    #[test]
+    fn test_top_bars_spread_into_flange() {
+        let drawing = section_drawing(
+            r#"
+            beam "VL-1":
+                L 80 55 15 25
+                cover 4
+                top 6 1"
+                bot 2 3/4"
+                ties 3/8" rto@20
+        "#,
+        );
+        let bars = bars(&drawing);
+        let top: Vec<_> = bars.iter().filter(|b| b.1 > 0.0).collect();
+        let bot: Vec<_> = bars.iter().filter(|b| b.1 < 0.0).collect();
+
+        // Bottom bars stay in the web on the left, top bars use the flange
+        assert!(bot.iter().all(|b| b.0 < -40.0 + 25.0));
+        let right_most = top.iter().map(|b| b.0).fold(f64::MIN, f64::max);
+        assert!((right_most - (40.0 - 4.0 - 0.9525 - 1.27)).abs() < 1e-9);
+    }
+
+    /// Measured length and text of every dimension in a drawing.
+    fn dimensions(drawing: &Drawing) -> Vec<(f64, String)> {
+        drawing
+            .primitives
+            .iter()
+            .filter_map(|p| match p {
+                Primitive::Dimension {
+                    start, end, text, ..
+                } => Some(((end.0 - start.0).hypot(end.1 - start.1), text.clone())),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_dimensions() {
+        let input = r#"
+            set:
+                dims on
+
+            beam "V-1":
+                30 x 60
+                cover 4
+                bot 3 1"
+                ties 3/8" rto@20
+                span 450
+                view both
+        "#;
+        let document = parser::parse(input).unwrap();
+        let drawings = generate(&document.sections[0], &document.defaults, &mut Vec::new());
+
+        let section = dimensions(&drawings[0]);
+        assert_eq!(
+            section,
+            vec![(30.0, "30".to_string()), (60.0, "60".to_string())]
+        );
+
+        let long: Vec<String> = dimensions(&drawings[1]).into_iter().map(|d| d.1).collect();
+        assert_eq!(long, vec!["20", "450", "20"]);
+    }
+
+    #[test]
+    fn test_dimensions_in_declared_units() {
+        let input = r#"
+            set:
+                units mm
+                dims on
+                labels table
+
+            beam "V-1":
+                300 x 600
+                bot 3 1"
+        "#;
+        let drawing = section_drawing(input);
+        let dims: Vec<String> = dimensions(&drawing).into_iter().map(|d| d.1).collect();
+        assert_eq!(dims, vec!["300", "600"]);
+
+        let table: Vec<String> = texts(&drawing).into_iter().map(|t| t.2).collect();
+        assert_eq!(&table[2..4], ["db (mm)", "As (mm²)"]);
+        assert_eq!(table[6], "25.4");
+    }
+
+    #[test]
+    fn test_full_dimensions() {
+        let drawing = section_drawing(
+            r#"
+            beam "V-1":
+                30 x 60
+                cover 4
+                bot 3 1"
+                ties 3/8" rto@20
+                dims full
+        "#,
+        );
+        let texts: Vec<String> = dimensions(&drawing).into_iter().map(|d| d.1).collect();
+
+        // Overall, two covers and two bar spacings between the bends
+        let spacing = seated(15.0, 2.54);
+        let spacing = annotations::format_length(spacing, Unit::Cm);
+        assert_eq!(texts, vec!["30", "60", "4", "4", &spacing, &spacing]);
+    }
+
+    #[test]
+    fn test_face_covers() {
+        let input = r#"
+            beam "V-1":
+                30 x 60
+                cover top 2.5 bot 5 sides 4
+                top 2 #4
+                bot 3 #6
+                ties #3 rto@20
+                dims full
+                span 300
+                view both
+        "#;
+        let document = parser::parse(input).unwrap();
+        let drawings = generate(&document.sections[0], &document.defaults, &mut Vec::new());
+        let stirrup = diameter("#3");
+
+        // The stirrup keeps each cover to its face
+        let (hoop, _) = paths(&drawings[0], "stirrup").remove(0);
+        let max = |f: fn(&(f64, f64)) -> f64| hoop.iter().map(f).fold(f64::MIN, f64::max);
+        assert!((max(|p| p.1) - (30.0 - 2.5 - stirrup / 2.0)).abs() < 1e-9);
+        assert!((max(|p| -p.1) - (30.0 - 5.0 - stirrup / 2.0)).abs() < 1e-9);
+        assert!((max(|p| p.0.abs()) - (15.0 - 4.0 - stirrup / 2.0)).abs() < 1e-9);
+
+        // The middle bottom bar rests on the stirrup above the bottom cover
+        let placed = bars(&drawings[0]);
+        let middle = placed.iter().find(|b| b.0.abs() < 1e-9).unwrap();
+        assert!((middle.1 - middle.2 / 2.0 - (-30.0 + 5.0 + stirrup)).abs() < 1e-9);
+
+        // The bottom and top covers are both dimensioned
+        let texts: Vec<String> = dimensions(&drawings[0]).into_iter().map(|d| d.1).collect();
+        assert_eq!(&texts[2..5], ["5", "2.5", "4"]);
+
+        // Along the beam, ties and bars follow the top and bottom covers
+        let rects: Vec<(f64, f64)> = drawings[1]
+            .primitives
+            .iter()
+            .filter_map(|p| match p {
+                Primitive::Rect {
+                    y,
+                    height,
+                    group: Some(g),
+                    ..
+                } if g == "stirrup_long" => Some((*y, y + height)),
+                _ => None,
+            })
+            .collect();
+        assert!(
+            rects
+                .iter()
+                .all(|r| (r.0 + 25.0).abs() < 1e-9 && (r.1 - 27.5).abs() < 1e-9)
+        );
+    }
+
+    #[test]
+    fn test_rect_perimeter() {
+        let drawing = section_drawing(
+            r#"
+            column "C-1":
+                40 x 60
+                cover 4
+                perim 12 #6
+                ties #3 rto@15
+        "#,
+        );
+        let bars = bars(&drawing);
+        assert_eq!(bars.len(), 12);
+
+        let d = diameter("#6");
+        let x = 20.0 - 4.0 - diameter("#3") - d / 2.0;
+        let y = 30.0 - 4.0 - diameter("#3") - d / 2.0;
+        let on = |a: f64, b: f64| (a - b).abs() < 1e-9;
+
+        // Four corners in the bends, every other bar on the stirrup
+        let corner =
+            |b: &&(f64, f64, f64)| on(b.0.abs(), seated(20.0, d)) && on(b.1.abs(), seated(30.0, d));
+        assert_eq!(bars.iter().filter(corner).count(), 4);
+        assert!(
+            bars.iter()
+                .filter(|b| !corner(b))
+                .all(|b| on(b.0.abs(), x) || on(b.1.abs(), y))
+        );
+
+        // The longer faces carry more bars
+        let left = bars.iter().filter(|b| on(b.0, -x)).count();
+        let top = bars.iter().filter(|b| on(b.1, y)).count();
+        assert_eq!((left, top), (3, 1));
+    }
+
+    #[test]
+    fn test_bar_groups_share_one_layer() {
+        let drawing = section_drawing(
+            r#"
+            beam "V-1":
+                30 x 60
+                cover 4
+                bot 2 3/4" 2 1"
+                ties 3/8" rto@20
+        "#,
+        );
+        let bars = bars(&drawing);
+        assert_eq!(bars.len(), 4);
+
+        // Left to right in declaration order
+        assert!(bars.windows(2).all(|w| w[0].0 < w[1].0));
+        assert!((bars[0].2 - 1.905).abs() < 1e-9);
+        assert!((bars[3].2 - 2.54).abs() < 1e-9);
+
+        // The inner bars rest on the stirrup, the outer ones sit in its bends
+        for (_, y, d) in &bars[1..3] {
+            let bottom = y - d / 2.0;
+            assert!((bottom - (-30.0 + 4.0 + 0.9525)).abs() < 1e-9);
+        }
+        for (x, y, d) in [bars[0], bars[3]] {
+            assert!((x.abs() - seated(15.0, d)).abs() < 1e-9);
+            assert!((y + seated(30.0, d)).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_explicit_layers() {
+        // The second layer is declared first, and gathers bars from two lines
+        let drawing = section_drawing(
+            r#"
+            beam "V-1":
+                30 x 60
+                cover 4
+                layer spacing 3
+                layer bot 2:
+                    2 #6
+                    1 #4
+                bot 3 #8
+                ties #3 rto@20
+        "#,
+        );
+        let bottoms: Vec<f64> = bars(&drawing)
+            .iter()
+            .map(|(_, y, d)| y - d / 2.0 + 30.0)
+            .collect();
+        let stirrup = 4.0 + diameter("#3");
+        assert_eq!(bottoms.len(), 6);
+
+        // Layer 2 rests on the largest bar of layer 1 plus the layer spacing;
+        // only the middle bar of layer 1 rests on the stirrup, between the bends
+        let layer_2 = stirrup + diameter("#8") + 3.0;
+        assert_eq!(
+            bottoms
+                .iter()
+                .filter(|b| (*b - stirrup).abs() < 1e-9)
+                .count(),
+            1
+        );
+        assert_eq!(
+            bottoms
+                .iter()
+                .filter(|b| (*b - layer_2).abs() < 1e-9)
+                .count(),
+            3
+        );
+    }
+
+    #[test]
+    fn test_corner_bars() {
+        let column = "column \"C-1\":\n  40 x 40\n  cover 4\n  ties #3 rto@15\n  corners 4 #8\n";
+        let drawing = section_drawing(&format!("{}  top 2 #5\n  sides 1 #5\n", column));
+        let placed = bars(&drawing);
+        assert_eq!(placed.len(), 8);
+
+        // Each corner bar touches the inside of its bend, on the diagonal
+        let bend_radius = 2.0 * diameter("#3");
+        let bend_center = 20.0 - 4.0 - diameter("#3") - bend_radius;
+        let corner = bend_center + (bend_radius - diameter("#8") / 2.0) / 2.0_f64.sqrt();
+        assert!((placed[0].0 + corner).abs() < 1e-9 && (placed[0].1 - corner).abs() < 1e-9);
+        assert!((placed[2].0 - corner).abs() < 1e-9 && (placed[2].1 + corner).abs() < 1e-9);
+
+        // Top bars split the distance between the top corners in three
+        let top: Vec<f64> = placed[4..6].iter().map(|b| b.0).collect();
+        assert!((top[0] + corner / 3.0).abs() < 1e-9);
+        assert!((top[1] - corner / 3.0).abs() < 1e-9);
+
+        // Side bars sit halfway between the corners, not between the layers
+        assert!(placed[6..].iter().all(|b| b.1.abs() < 1e-9));
+
+        // A perimeter line only fills the faces
+        let drawing = section_drawing(&format!("{}  perim 4 #5\n", column));
+        let faces = &bars(&drawing)[4..];
+        assert_eq!(faces.iter().filter(|b| b.0.abs() < 1e-9).count(), 2);
+        assert_eq!(faces.iter().filter(|b| b.1.abs() < 1e-9).count(), 2);
+    }
+
+    #[test]
+    fn test_bend_radius() {
+        let beam = "beam \"V-1\":\n  40 x 60\n  cover 4\n  ties #3 rto@20\n";
+
+        // A thick bar away from the bends leaves the mandrel of the stirrup
+        let drawing = section_drawing(&format!("{}  bot 2 #6\n  mid 2 #18\n", beam));
+        let corner = seated(20.0, diameter("#6"));
+        assert!((bars(&drawing)[0].0 + corner).abs() < 1e-9);
+
+        // A corner bar thicker than the mandrel bends the stirrup around it,
+        // touching both legs
+        let drawing = section_drawing(&format!("{}  corners 4 #14\n", beam));
+        let corner = 20.0 - 4.0 - diameter("#3") - diameter("#14") / 2.0;
+        assert!((bars(&drawing)[0].0 + corner).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_spaced_bars() {
+        let wall =
+            "set:\n  labels legend\n\nwall \"M-1\":\n  100 x 20\n  cover 4\n  ties 3/8\" rto@20\n";
+        let drawing = section_drawing(&format!("{}  top 1/2\" @ 20\n  bot #4 @ 25\n", wall));
+        let placed = bars(&drawing);
+
+        // 88.8 between the outer centers fits five bars at 20; the leftover
+        // is shared by both ends
+        let top: Vec<f64> = placed.iter().filter(|b| b.1 > 0.0).map(|b| b.0).collect();
+        assert_eq!(top, vec![-40.0, -20.0, 0.0, 20.0, 40.0]);
+        let bot: Vec<f64> = placed.iter().filter(|b| b.1 < 0.0).map(|b| b.0).collect();
+        assert_eq!(bot, vec![-37.5, -12.5, 12.5, 37.5]);
+
+        let contents: Vec<String> = texts(&drawing).into_iter().map(|t| t.2).collect();
+        assert_eq!(contents, vec!["top: Ø1/2\"@20", "bot: #4@25"]);
+
+        // Between corner bars, the fewest bars that keep the spacing
+        let column = "column \"C-1\":\n  40 x 40\n  cover 4\n  ties #3 rto@15\n  corners 4 #8\n";
+        let drawing = section_drawing(&format!("{}  top #4 @ 10\n", column));
+        let placed = bars(&drawing);
+        let corner = placed[1].0;
+        assert_eq!(placed.len(), 6);
+        assert!((placed[4].0 + corner / 3.0).abs() < 1e-9);
+        assert!((placed[5].0 - corner / 3.0).abs() < 1e-9);
+
+        // The lines report the bars drawn
+        let beam = "beam \"V-1\":\n  30 x 60\n  cover 4\n  top #5 @ 15\n  bot #5 @ 5\n";
+        let rebar = parser::parse(beam).unwrap().sections[0]
+            .properties
+            .rebar
+            .clone();
+        let placed = bars(&section_drawing(beam));
+        let top = placed.iter().filter(|b| b.1 > 0.0).count() as u32;
+        let bot = placed.iter().filter(|b| b.1 < 0.0).count() as u32;
+        assert_eq!((rebar[0].count(), rebar[1].count()), (top, bot));
+        assert_eq!((top, bot), (2, 4));
+    }
+
+    #[test]
+    fn test_explicit_bars() {
+        let beam = "beam \"V-1\":\n  30 x 60\n  cover 4\n  ties #3 rto@20\n";
+        let drawing = section_drawing(&format!(
+            "{}  bar 1\" at (5, 55)\n  bar #6 at (-3, 0)\n",
+            beam
+        ));
+        let placed = bars(&drawing);
+        assert_eq!(placed.len(), 2);
+        assert!((placed[0].0 + 10.0).abs() < 1e-9 && (placed[0].1 - 25.0).abs() < 1e-9);
+        assert!((placed[1].0 + 18.0).abs() < 1e-9 && (placed[1].1 + 30.0).abs() < 1e-9);
+
+        // From the centroid of an L section, which lies towards its web
+        let ell = "set:\n  origin centroid\n\nbeam \"V-1\":\n  L 60 50 10 20\n  bar #6 at (0, 0)\n";
+        let placed = bars(&section_drawing(ell));
+        let (flange, web) = (60.0 * 10.0, 20.0 * 40.0);
+        let x = web * -20.0 / (flange + web);
+        let y = (flange * 20.0 + web * -5.0) / (flange + web);
+        assert!((placed[0].0 - x).abs() < 1e-9 && (placed[0].1 - y).abs() < 1e-9);
+
+        // Explicit bars take part in the checks of the other lines
+        let warnings = warnings(&format!("{}  top 2 1\"\n  bar 1\" at (7, 55)\n", beam));
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].code, "bars-overlap");
+        assert_eq!(warnings[0].message, "`top` and `bar` bars overlap");
+    }
+
+    /// Points of every path of a group, and whether it is closed.
+    fn paths(drawing: &Drawing, group: &str) -> Vec<(Vec<(f64, f64)>, bool)> {
+        drawing
+            .primitives
+            .iter()
+            .filter_map(|p| match p {
+                Primitive::Path {
+                    points,
+                    closed,
+                    group: Some(g),
+                    ..
+                } if g == group => Some((points.clone(), *closed)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_tie_kinds() {
+        let column = "column \"C-1\":\n  60 x 40\n  cover 4\n  span 300\n  view both\n  top 4 #6\n  bot 4 #6\n  sides 1 #6\n  ties:\n    outer overlapping #3 rto@15\n    inner cross #3 rto@30\n";
+        let document = parser::parse(column).unwrap();
+        let drawings = generate(&document.sections[0], &document.defaults, &mut Vec::new());
+
+        // Both hoops wrap the two interior bars of each face
+        let hoops = paths(&drawings[0], "stirrup");
+        assert_eq!(hoops.len(), 2);
+        let interior: Vec<f64> = bars(&drawings[0])
+            .iter()
+            .map(|b| b.0)
+            .filter(|x| x.abs() < 10.0)
+            .collect();
+        assert_eq!(interior.len(), 4);
+        for (points, closed) in &hoops {
+            assert!(!closed);
+            let min = points.iter().map(|p| p.0).fold(f64::INFINITY, f64::min);
+            let max = points.iter().map(|p| p.0).fold(f64::NEG_INFINITY, f64::max);
+            assert!(interior.iter().all(|x| *x > min && *x < max));
+        }
+
+        // One crosstie each way, with a hook at both ends
+        let crossties = paths(&drawings[0], "stirrup_inner");
+        assert_eq!(crossties.len(), 2);
+        assert!(
+            crossties
+                .iter()
+                .all(|(points, closed)| points.len() == 4 && !closed)
+        );
+        // Each end rests on a bar of the face
+        let touches = |drawing: &Drawing, (x, y): (f64, f64)| {
+            bars(drawing)
+                .iter()
+                .any(|b| (b.0 - x).hypot(b.1 - y) <= (b.2 + diameter("#3")) / 2.0 + 1e-9)
+        };
+        for (points, _) in &crossties {
+            assert!(touches(&drawings[0], points[1]) && touches(&drawings[0], points[2]));
+        }
+        assert!(crossties[0].0[1..3].iter().all(|p| p.0.abs() > 1.0));
+        assert!(crossties[1].0[1..3].iter().all(|p| p.1.abs() < 1e-9));
+
+        // Without side bars there is nothing for the horizontal one
+        let column = column.replace("  sides 1 #6\n", "");
+        let drawing = section_drawing(&column);
+        assert_eq!(paths(&drawing, "stirrup_inner").len(), 1);
+        let reported = warnings(&column);
+        assert_eq!(reported.len(), 1);
+        assert_eq!(reported[0].code, "tie-without-bars");
+        assert_eq!(reported[0].line, 10);
+
+        // A diamond has a corner at the middle bar of each face
+        let diamond = "column \"C-1\":\n  40 x 40\n  cover 4\n  top 3 #6\n  bot 3 #6\n  sides 1 #6\n  ties:\n    outer #3 rto@15\n    inner diamond #3 rto@15\n";
+        let drawing = section_drawing(diamond);
+        let (points, closed) = paths(&drawing, "stirrup_inner").remove(0);
+        assert!(closed);
+        assert!(points.iter().all(|p| touches(&drawing, *p)));
+
+        // 19 outer ties at 15 and 9 crossties at 30 along 292
+        let long = drawings[1]
+            .primitives
+            .iter()
+            .filter(|p| matches!(p, Primitive::Rect { group: Some(g), .. } if g == "stirrup_long"))
+            .count();
+        assert_eq!(long, 19 + 9);
+
+        // A U stirrup ends at the top of both legs
+        let slab = "wall \"L-1\":\n  100 x 20\n  cover 2.5\n  bot #4 @ 20\n  ties:\n    outer open-top #3 rto@20\n";
+        let stirrup = paths(&section_drawing(slab), "stirrup").remove(0);
+        assert!(!stirrup.1);
+        let top = 10.0 - 2.5 - diameter("#3") / 2.0;
+        assert!((stirrup.0[0].1 - top).abs() < 1e-9);
+        assert!((stirrup.0.last().unwrap().1 - top).abs() < 1e-9);
+
+        // Circular sections only take closed hoops
+        let circle = "column \"C-1\":\n  D 50\n  perim 8 #6\n  ties:\n    outer #3 rto@15\n    inner diamond #3 rto@15\n";
+        let warnings = warnings(circle);
+        assert_eq!(warnings.len(), 2);
+        assert_eq!(warnings[1].code, "tie-ignored");
+        assert_eq!(warnings[1].line, 6);
+    }
+
+    #[test]
+    fn test_tie_hooks() {
+        let column = "column \"C-1\":\n  40 x 40\n  cover 4\n  top 3 #6\n  bot 3 #6\n  ties:\n    outer #3 rto@15 hook bottom-right\n    inner cross #3 rto@30 hook 90\n";
+        let drawing = section_drawing(column);
+
+        // Both ends hook into the core from the bottom-right corner, past
+        // the 135° bends, with the 7.5cm minimum for a #3
+        let (hoop, closed) = paths(&drawing, "stirrup").remove(0);
+        assert!(!closed);
+        let edge = 20.0 - 4.0 - diameter("#3") / 2.0;
+        let length = |a: (f64, f64), b: (f64, f64)| (a.0 - b.0).hypot(a.1 - b.1);
+        for (tip, bend) in [
+            (hoop[0], hoop[1]),
+            (hoop[hoop.len() - 1], hoop[hoop.len() - 2]),
+        ] {
+            assert!((length(tip, bend) - 7.5).abs() < 1e-9);
+            assert!(tip.0 < bend.0 && tip.1 > bend.1);
+            assert!(bend.0 > 0.0 && bend.1 < 0.0 && bend.0.max(-bend.1) < edge);
+        }
+
+        // Crossties with 90° hooks at both ends
+        for (points, _) in paths(&drawing, "stirrup_inner") {
+            let leg = (points[2].0 - points[1].0, points[2].1 - points[1].1);
+            for (tip, bend) in [(points[0], points[1]), (points[3], points[2])] {
+                let hook = (tip.0 - bend.0, tip.1 - bend.1);
+                assert!((hook.0 * leg.0 + hook.1 * leg.1).abs() < 1e-9);
+            }
+        }
+
+        // Without `hook`, hoops close at the top-left corner
+        let beam = "beam \"V-1\":\n  30 x 60\n  bot 3 #6\n  ties #3 rto@20\n";
+        let (hoop, _) = paths(&section_drawing(beam), "stirrup").remove(0);
+        assert!(hoop[0].0 < 0.0 && hoop[0].1 > 0.0);
+    }
+
+    #[test]
+    fn test_spiral() {
+        let column = "column \"C-1\":\n  D 50\n  cover 4\n  span 300\n  view both\n  perim 8 #6\n  spiral #3 pitch 5\n";
+        let document = parser::parse(column).unwrap();
+        let mut reported = Vec::new();
+        let drawings = generate(&document.sections[0], &document.defaults, &mut reported);
+        assert!(reported.is_empty());
+
+        // The hoop in the section, and half a turn per line along the column
+        let radius = 25.0 - 4.0 - diameter("#3") / 2.0;
+        assert!(drawings[0].primitives.iter().any(|p| matches!(
+            p,
+            Primitive::Circle { radius: r, group: Some(g), .. }
+                if g == "stirrup" && (r - radius).abs() < 1e-9
+        )));
+        let (helix, _) = paths(&drawings[1], "stirrup_long").remove(0);
+        assert_eq!(helix.len(), 3 + 2 * 58 + 4);
+        assert_eq!(helix[0], (4.0, radius));
+        assert!((helix[3].0 - 4.0 - 1.5 * diameter("#3")).abs() < 1e-9);
+        assert_eq!(helix.last().map(|p| p.1), Some(radius));
+
+        // 58 turns of at most 5 along the column and 1.5 more at each end
+        let texts = texts(&drawings[1]);
+        assert_eq!(texts[0].2, "spiral #3: 61 turns, L = 7871.82cm");
+
+        // A span shorter than the closed end turns draws no spiral
+        let short = column.replace("span 300", "span 10");
+        let reported = warnings(&short);
+        assert_eq!(reported.len(), 1);
+        assert_eq!(reported[0].code, "spiral-too-short");
+        assert_eq!(reported[0].line, 7);
+        let document = parser::parse(&short).unwrap();
+        let drawings = generate(&document.sections[0], &document.defaults, &mut Vec::new());
+        assert!(drawings[1].primitives.iter().all(|p| !matches!(
+            p,
+            Primitive::Path { group: Some(g), .. } if g == "stirrup_long"
+        )));
+
+        // Only circular sections take a spiral
+        let beam = "beam \"V-1\":\n  30 x 60\n  cover 4\n  spiral #3 pitch 5\n";
+        let codes: Vec<String> = warnings(beam).into_iter().map(|w| w.code).collect();
+        assert_eq!(codes, vec!["tie-ignored"]);
+    }
+
+    /// Position and content of every text in a drawing.
+    fn texts(drawing: &Drawing) -> Vec<(f64, f64, String)> {
+        drawing
+            .primitives
+            .iter()
+            .filter_map(|p| match p {
+                Primitive::Text { x, y, content, .. } => Some((*x, *y, content.clone())),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_callout_labels() {
+        let drawing = section_drawing(
+            r#"
+            beam "V-1":
+                30 x 60
+                cover 4
+                top 2 1"
+                bot 2 3/4" 2 1"
+                ties 3/8" rto@20
+                labels callout
+        "#,
+        );
+        let texts = texts(&drawing);
+        let contents: Vec<&str> = texts.iter().map(|t| t.2.as_str()).collect();
+        // Top to bottom: the larger bottom bars sit slightly higher
+        assert_eq!(contents, vec!["2Ø1\"", "2Ø1\"", "2Ø3/4\""]);
+
+        // Right of the section, the two bottom groups stacked apart
+        assert!(texts.iter().all(|t| t.0 > 15.0));
+        assert!(texts[1].1 - texts[2].1 >= 4.0 - 1e-9);
+
+        let leaders = drawing
+            .primitives
+            .iter()
+            .filter(|p| matches!(p, Primitive::Path { group: Some(g), .. } if g == "label"))
+            .count();
+        assert_eq!(leaders, 3);
+    }
+
+    #[test]
+    fn test_legend_labels() {
+        let input = r#"
+            set:
+                labels legend
+                dims on
+
+            column "C-1":
+                40 x 40
+                cover 4
+                top 3 3/4"
+                bot 3 3/4"
+                sides 2 1/2"
+                ties 3/8" rto@20
+        "#;
+        let drawing = section_drawing(input);
+        let texts = texts(&drawing);
+        let contents: Vec<&str> = texts.iter().map(|t| t.2.as_str()).collect();
+
+        // Side bars count both faces
+        assert_eq!(
+            contents,
+            vec!["top: 3Ø3/4\"", "bot: 3Ø3/4\"", "sides: 4Ø1/2\""]
+        );
+        assert!(texts.iter().all(|t| t.1 < -20.0 - 6.0));
+    }
+
+    #[test]
+    fn test_table_labels() {
+        let drawing = section_drawing(
+            r#"
+            column "C-1":
+                40 x 60
+                cover 4
+                perim 12 #6
+                ties #3 rto@15
+                labels table
+        "#,
+        );
+        let contents: Vec<String> = texts(&drawing).into_iter().map(|t| t.2).collect();
+
+        let d = diameter("#6");
+        let area = annotations::format_length(12.0 * 2.84, Unit::Cm);
+        assert_eq!(
+            contents,
+            vec![
+                "n",
+                "Ø",
+                "db (cm)",
+                "As (cm²)",
+                "12",
+                "#6",
+                &annotations::format_length(d, Unit::Cm),
+                &area,
+                "Total",
+                &area,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_warnings() {
+        let codes = |input: &str| {
+            warnings(input)
+                .into_iter()
+                .map(|w| (w.code, w.line))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            codes("beam \"V-1\":\n  30 x 60\n  cover 4\n  top 2 #6\n  bot 3 #6\n"),
+            vec![]
+        );
+        assert_eq!(
+            codes("column \"C-1\":\n  D 50\n  cover 4\n  sides 2 #5\n  view both\n"),
+            vec![
+                ("pattern-ignored".to_string(), 4),
+                ("missing-span".to_string(), 1),
+            ]
+        );
+        assert_eq!(
+            codes("beam \"V-1\":\n  30 x 60\n  top 2 #6\n"),
+            vec![("default-cover".to_string(), 1)]
+        );
+    }
+
+    #[test]
+    fn test_layers_in_circles_and_polygons() {
+        let drawings = |input: &str| {
+            let document = parser::parse(input).unwrap();
+            let mut warnings = Vec::new();
+            let drawings = generate(&document.sections[0], &document.defaults, &mut warnings);
+            let codes: Vec<(String, usize)> =
+                warnings.into_iter().map(|w| (w.code, w.line)).collect();
+            (drawings, codes)
+        };
+        let long_bars = |drawing: &Drawing| {
+            drawing
+                .primitives
+                .iter()
+                .filter(
+                    |p| matches!(p, Primitive::Rect { group: Some(g), .. } if g == "rebar_long"),
+                )
+                .count()
+        };
+
+        // Neither view draws layers in a circle
+        let (circle, codes) = drawings(
+            "column \"C-1\":\n  D 50\n  cover 4\n  perim 6 #6\n  top 2 #5\n  span 300\n  view both\n",
+        );
+        assert_eq!(codes, vec![("pattern-ignored".to_string(), 5)]);
+        assert_eq!(bars(&circle[0]).len(), 6);
+        assert_eq!(long_bars(&circle[1]), 0);
+
+        // Nor `mid` bars in a polygon
+        let (polygon, codes) = drawings(&format!(
+            "{}  top 4 #6\n  mid 2 #5\n  span 300\n  view both\n",
+            HOLLOW_BOX
+        ));
+        assert_eq!(codes, vec![("pattern-ignored".to_string(), 16)]);
+        assert_eq!(long_bars(&polygon[1]), 1);
+    }
+
+    #[test]
+    fn test_bars_outside() {
+        let codes = |input: &str| {
+            warnings(input)
+                .into_iter()
+                .map(|w| (w.code, w.line))
+                .collect::<Vec<_>>()
+        };
+        let beam = "beam \"V-1\":\n  30 x 60\n  cover 4\n";
+        assert_eq!(codes(&format!("{}  bar #6 at (10, 50)\n", beam)), vec![]);
+        assert_eq!(
+            codes(&format!("{}  bar #6 at (100, 100)\n", beam)),
+            vec![("bar-outside".to_string(), 4)]
+        );
+        // Crossing the outline
+        assert_eq!(
+            codes(&format!("{}  bar #6 at (0.5, 30)\n", beam)),
+            vec![("bar-outside".to_string(), 4)]
+        );
+        assert_eq!(
+            codes("column \"C-1\":\n  D 40\n  cover 4\n  bar #6 at (39.5, 20)\n"),
+            vec![("bar-outside".to_string(), 4)]
+        );
+
+        // In the void of a polygon, measured from the bottom-left of its
+        // bounding box
+        let codes = |input: &str| {
+            warnings(&format!("{}{}", HOLLOW_BOX, input))
+                .into_iter()
+                .map(|w| w.code)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(codes("  bar #6 at (10, 10)\n"), Vec::<String>::new());
+        assert_eq!(
+            codes("  bar #6 at (50, 40)\n"),
+            vec!["bar-outside".to_string()]
+        );
+    }
+
+    /// Hollow box with its outline written clockwise and signed coordinates.
+    const HOLLOW_BOX: &str = "beam \"V-1\":\n  shape:\n    polygon:\n      (-50, 0)\n      (-50, 80)\n      (50, 80)\n      (50, 0)\n    void \"cell\":\n      polygon:\n        (-35, 15)\n        (35, 15)\n        (35, 60)\n        (-35, 60)\n  cover 5\n";
+
+    #[test]
+    fn test_polygon_section() {
+        let drawing = section_drawing(&format!("{}  top 4 #6\n  bot 2 #6\n", HOLLOW_BOX));
+
+        // The outline, counter-clockwise about the middle of its bounding box,
+        // and the void
+        let concrete = paths(&drawing, "concrete");
+        assert_eq!(concrete.len(), 2);
+        assert!(concrete.iter().all(|(_, closed)| *closed));
+        assert_eq!(concrete[0].0[0], (-50.0, -40.0));
+        assert_eq!(concrete[0].0[1], (50.0, -40.0));
+        assert_eq!(concrete[1].0[0], (-35.0, -25.0));
+
+        // The tie, of the default size, follows the outline at the cover
+        let stirrup = 0.95;
+        let tie = &paths(&drawing, "stirrup")[0].0;
+        let edge = 50.0 - 5.0 - stirrup / 2.0;
+        let inner = 40.0 - 5.0 - stirrup / 2.0;
+        for (point, expected) in
+            tie.iter()
+                .zip([(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)])
+        {
+            assert!((point.0 - expected.0 * edge).abs() < 1e-9);
+            assert!((point.1 - expected.1 * inner).abs() < 1e-9);
+        }
+
+        // Layers against the tie, spread across the outline
+        let d = diameter("#6");
+        let face = 40.0 - 5.0 - stirrup - d / 2.0;
+        let bars = bars(&drawing);
+        let top: Vec<_> = bars.iter().filter(|b| (b.1 - face).abs() < 1e-9).collect();
+        let bottom: Vec<_> = bars.iter().filter(|b| (b.1 + face).abs() < 1e-9).collect();
+        assert_eq!((top.len(), bottom.len()), (4, 2));
+        let side = 50.0 - 5.0 - stirrup - d / 2.0;
+        assert!((top[0].0 + side).abs() < 1e-9);
+        assert!((top[3].0 - side).abs() < 1e-9);
+        assert!((top[1].0 - top[0].0 - 2.0 * side / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_polygon_perimeter() {
+        let trapezoid = "column \"C-1\":\n  shape:\n    polygon:\n      (0, 0)\n      (100, 0)\n      (80, 40)\n      (20, 40)\n  cover 4\n  ties #3 rto@15\n";
+
+        // A bar at every vertex, then the longest edges take the rest
+        let bars = bars(&section_drawing(&format!("{}  perim 6 #5\n", trapezoid)));
+        assert_eq!(bars.len(), 6);
+        let low = bars.iter().map(|b| b.1).fold(f64::INFINITY, f64::min);
+        let high = bars.iter().map(|b| b.1).fold(f64::NEG_INFINITY, f64::max);
+        let count = |y: f64| bars.iter().filter(|b| (b.1 - y).abs() < 1e-9).count();
+        assert_eq!((count(low), count(high)), (3, 3));
+        assert!((low + 20.0 - 4.0 - diameter("#3") - diameter("#5") / 2.0).abs() < 1e-9);
+
+        // Bars at a point are measured from the bottom-left of the bounding
+        // box, or from the centroid of the concrete less its voids
+        let bar = bars_at(&format!("{}  bar #5 at (10, 20)\n", HOLLOW_BOX));
+        assert_eq!(bar, (-40.0, -20.0));
+        let (x, y) = bars_at(&format!(
+            "{}  bar #5 at (0, 0)\n  origin centroid\n",
+            HOLLOW_BOX
+        ));
+        assert!(x.abs() < 1e-9);
+        assert!((y - 3150.0 * 2.5 / (8000.0 - 3150.0)).abs() < 1e-9);
+    }
+
+    fn bars_at(input: &str) -> (f64, f64) {
+        let bar = bars(&section_drawing(input))[0];
+        (bar.0, bar.1)
+    }
+
+    #[test]
+    fn test_polygon_warnings() {
+        let codes = |input: &str| {
+            warnings(&format!("{}{}", HOLLOW_BOX, input))
+                .into_iter()
+                .map(|w| w.code)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(codes("  top 4 #6\n  bot 4 #6\n"), Vec::<String>::new());
+        assert_eq!(codes("  mid 2 #5\n"), vec!["pattern-ignored".to_string()]);
+        assert_eq!(
+            codes("  corners 4 #6\n"),
+            vec!["pattern-ignored".to_string()]
+        );
+        assert_eq!(
+            codes("  ties:\n    outer #3 rto@20\n    inner cross #3 rto@20\n"),
+            vec!["tie-ignored".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_clear_spacing() {
+        let messages = |input: &str| {
+            warnings(input)
+                .into_iter()
+                .map(|w| (w.code, w.message))
+                .collect::<Vec<_>>()
+        };
+        let beam = "beam \"V-1\":\n  30 x 60\n  cover 4\n  ties #3 rto@20\n";
+
+        // The outer bars sit in the bends 17.18 cm apart, leaving
+        // 17.18 - 4 * 2.54 = 7.02 cm shared by four gaps
+        assert_eq!(
+            messages(&format!("{}  bot 5 1\"\n", beam)),
+            vec![(
+                "clear-spacing".to_string(),
+                "`bot` bars are 1.76cm apart, less than the minimum of 2.54cm".to_string()
+            )]
+        );
+
+        // 6.05 cm between three bars is enough, unless 4/3 of the aggregate is more
+        assert_eq!(messages(&format!("{}  bot 3 1\"\n", beam)), vec![]);
+        assert_eq!(
+            messages(&format!("{}  aggregate 5\n  bot 3 1\"\n", beam)),
+            vec![(
+                "clear-spacing".to_string(),
+                "`bot` bars are 6.05cm apart, less than the minimum of 6.67cm".to_string()
+            )]
+        );
+
+        // Layers closer than the minimum; the closest pair is diagonal, as the
+        // smaller bars sit further out
+        let warnings = warnings(&format!(
+            "{}  layer spacing 1\n  top 2 1\"\n  top 2 #4\n",
+            beam
+        ));
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].code, "layer-spacing");
+        assert_eq!(warnings[0].line, 7);
+        assert_eq!(
+            warnings[0].message,
+            "`top` layers are 0.94cm apart, less than the minimum of 2.5cm"
+        );
+
+        // Separate `bar` lines are single bars, not layers
+        assert_eq!(
+            messages(&format!(
+                "{}  bar #6 at (10, 30)\n  bar #6 at (13, 30)\n",
+                beam
+            )),
+            vec![(
+                "clear-spacing".to_string(),
+                "`bar` bars at lines 5 and 6 are 1.1cm apart, less than the minimum of 2.5cm"
+                    .to_string()
+            )]
+        );
+    }
+}
@@ -0,0 +1,424 @@
+use super::{
+    Drawing, LAYER_SPACING, PlacedBar, Primitive, Stroke, flange_x_range, shape_height,
+    shape_width, web_x_range,
+};
+use crate::parser::ast::{BarGroup, Cover, Dims, Labels, RebarEntry, Shape, Unit};
+
+/// Distance between the outline and each row of dimensions, in cm.
+const DIM_GAP: f64 = 6.0;
+
+/// Distance between the outline and the labels, and between lines of
+/// labels, in cm.
+const LABEL_GAP: f64 = 4.0;
+
+/// Horizontal position of each column of the `table` mode, from its left edge.
+const TABLE_COLUMNS: [f64; 4] = [0.0, 8.0, 16.0, 26.0];
+
+/// Overall dimensions of the section; `Dims::Full` adds a first row with the
+/// cover and the spacing of the bottom layer, and the top cover if it is not
+/// the bottom one.
+pub fn section_dimensions(
+    drawing: &mut Drawing,
+    shape: &Shape,
+    mode: &Dims,
+    cover: &Cover,
+    bars: &[PlacedBar],
+    units: Unit,
+) {
+    let width = shape_width(shape);
+    let height = shape_height(shape);
+    let (left, right) = web_x_range(shape);
+    let top = height / 2.0;
+    let bottom = -top;
+
+    let outer = match mode {
+        Dims::Full => 2.0 * DIM_GAP,
+        _ => DIM_GAP,
+    };
+
+    if let Shape::Circle { diameter } = shape {
+        add_dimension(
+            drawing,
+            (-diameter / 2.0, bottom),
+            (diameter / 2.0, bottom),
+            -outer,
+            format!("Ø{}", format_length(*diameter, units)),
+        );
+        if let Dims::Full = mode {
+            add_dimension(
+                drawing,
+                (diameter / 2.0 - cover.sides, 0.0),
+                (diameter / 2.0, 0.0),
+                0.0,
+                format_length(cover.sides, units),
+            );
+        }
+        return;
+    }
+
+    match flange_x_range(shape) {
+        // Flanged sections: flange width above, web width below
+        Some((flange_left, flange_right)) => {
+            add_dimension(
+                drawing,
+                (flange_left, top),
+                (flange_right, top),
+                DIM_GAP,
+                format_length(width, units),
+            );
+            add_dimension(
+                drawing,
+                (left, bottom),
+                (right, bottom),
+                -outer,
+                format_length(right - left, units),
+            );
+        }
+        None => add_dimension(
+            drawing,
+            (left, bottom),
+            (right, bottom),
+            -outer,
+            format_length(width, units),
+        ),
+    }
+
+    // Height on the left, measured from the web so the extension lines
+    // start at the outline
+    add_dimension(
+        drawing,
+        (left, bottom),
+        (left, top),
+        outer + left + width / 2.0,
+        format_length(height, units),
+    );
+
+    if let Dims::Full = mode {
+        add_dimension(
+            drawing,
+            (right, bottom),
+            (right, bottom + cover.bottom),
+            -DIM_GAP,
+            format_length(cover.bottom, units),
+        );
+        if cover.top != cover.bottom {
+            add_dimension(
+                drawing,
+                (right, top - cover.top),
+                (right, top),
+                -DIM_GAP,
+                format_length(cover.top, units),
+            );
+        }
+        add_dimension(
+            drawing,
+            (left, bottom),
+            (left + cover.sides, bottom),
+            -DIM_GAP,
+            format_length(cover.sides, units),
+        );
+
+        // Center to center spacing of the layer resting on the bottom stirrup,
+        // including the bars seated a little higher in its bends
+        let lowest = bars
+            .iter()
+            .map(|b| b.y - b.diameter / 2.0)
+            .fold(f64::INFINITY, f64::min);
+        let mut layer: Vec<f64> = bars
+            .iter()
+            .filter(|b| b.y - b.diameter / 2.0 - lowest < LAYER_SPACING)
+            .map(|b| b.x)
+            .collect();
+        layer.sort_by(f64::total_cmp);
+
+        for pair in layer.windows(2) {
+            add_dimension(
+                drawing,
+                (pair[0], bottom),
+                (pair[1], bottom),
+                -DIM_GAP,
+                format_length(pair[1] - pair[0], units),
+            );
+        }
+    }
+}
+
+/// Support widths and clear span, chained below the beam.
+pub fn longitudinal_dimensions(
+    drawing: &mut Drawing,
+    span: f64,
+    height: f64,
+    support_width: f64,
+    units: Unit,
+) {
+    let bottom = -height / 2.0;
+    let stations = [-support_width, 0.0, span, span + support_width];
+
+    for pair in stations.windows(2) {
+        add_dimension(
+            drawing,
+            (pair[0], bottom),
+            (pair[1], bottom),
+            -DIM_GAP,
+            format_length(pair[1] - pair[0], units),
+        );
+    }
+}
+
+/// Turns and bar length of a spiral, under the longitudinal view and its
+/// dimensions, e.g. `spiral #3: 43.5 turns, L = 6912.4cm`.
+pub fn spiral_quantities(
+    drawing: &mut Drawing,
+    height: f64,
+    dims: Option<&Dims>,
+    size: &str,
+    (turns, length): (f64, f64),
+    units: Unit,
+) {
+    let below = match dims {
+        Some(Dims::On | Dims::Full) => DIM_GAP,
+        _ => 0.0,
+    };
+    let content = format!(
+        "spiral {}: {} turns, L = {}{}",
+        size,
+        format_number(turns),
+        format_length(length, units),
+        units.suffix()
+    );
+    add_text(drawing, (0.0, -height / 2.0 - below - LABEL_GAP), content);
+}
+
+/// Bar labels of the section view. Callouts go on the right of the outline;
+/// the legend and the table go under the drawing, below any dimensions.
+pub fn section_labels(
+    drawing: &mut Drawing,
+    shape: &Shape,
+    mode: &Labels,
+    dims: Option<&Dims>,
+    rebar: &[RebarEntry],
+    bars: &[PlacedBar],
+    units: Unit,
+) {
+    let groups = label_groups(rebar, bars);
+
+    if let Labels::Callout | Labels::Both = mode {
+        add_callouts(
+            drawing,
+            shape_width(shape) / 2.0 + LABEL_GAP,
+            &groups,
+            units,
+        );
+    }
+
+    let below = match dims {
+        Some(Dims::Full) => 2.0 * DIM_GAP,
+        Some(Dims::On) => DIM_GAP,
+        _ => 0.0,
+    };
+    let origin = (
+        -shape_width(shape) / 2.0,
+        -shape_height(shape) / 2.0 - below - LABEL_GAP,
+    );
+
+    match mode {
+        Labels::Legend | Labels::Both => add_legend(drawing, origin, rebar, &groups, units),
+        Labels::Table => add_table(drawing, origin, &groups, units),
+        Labels::Off | Labels::Callout => {}
+    }
+}
+
+/// The bars placed for one group of a rebar line.
+struct LabelGroup<'a> {
+    entry: usize,
+    group: &'a BarGroup,
+    spacing: Option<f64>,
+    bars: Vec<&'a PlacedBar>,
+}
+
+impl LabelGroup<'_> {
+    /// Count and size, e.g. `3Ø1"`, or size and spacing for a line given by
+    /// spacing, e.g. `Ø1/2"@20`.
+    fn label(&self, units: Unit) -> String {
+        match self.spacing {
+            Some(spacing) => format!(
+                "{}@{}",
+                size_label(&self.group.size),
+                format_length(spacing, units)
+            ),
+            None => bar_label(self.bars.len(), &self.group.size),
+        }
+    }
+}
+
+/// Placed bars gathered by group, in declaration order.
+fn label_groups<'a>(rebar: &'a [RebarEntry], bars: &'a [PlacedBar]) -> Vec<LabelGroup<'a>> {
+    let mut keys: Vec<(usize, usize)> = bars.iter().map(|b| (b.entry, b.group)).collect();
+    keys.sort();
+    keys.dedup();
+
+    keys.into_iter()
+        .map(|(entry, group)| {
+            let bars: Vec<&PlacedBar> = bars
+                .iter()
+                .filter(|b| b.entry == entry && b.group == group)
+                .collect();
+            LabelGroup {
+                entry,
+                group: &rebar[entry].groups[group],
+                spacing: rebar[entry].spacing,
+                bars,
+            }
+        })
+        .collect()
+}
+
+/// One callout per group, pointing at its right-most bar. Callouts are
+/// stacked top to bottom so that groups at the same height do not overlap.
+fn add_callouts(drawing: &mut Drawing, text_x: f64, groups: &[LabelGroup], units: Unit) {
+    let mut targets: Vec<(&PlacedBar, String)> = groups
+        .iter()
+        .filter_map(|g| {
+            let bar = g.bars.iter().max_by(|a, b| a.x.total_cmp(&b.x))?;
+            Some((*bar, g.label(units)))
+        })
+        .collect();
+    targets.sort_by(|a, b| b.0.y.total_cmp(&a.0.y));
+
+    let mut next_y = f64::INFINITY;
+    for (bar, label) in targets {
+        let y = bar.y.min(next_y);
+        next_y = y - LABEL_GAP;
+
+        drawing.add(Primitive::Path {
+            points: vec![(bar.x, bar.y), (text_x - 1.0, y)],
+            closed: false,
+            stroke: Some(Stroke {
+                color: "black".to_string(),
+                width: 0.5,
+            }),
+            fill: None,
+            group: Some("label".to_string()),
+        });
+        add_text(drawing, (text_x, y), label);
+    }
+}
+
+/// One line per rebar line, e.g. `bot: 2Ø3/4" + 2Ø1"`.
+fn add_legend(
+    drawing: &mut Drawing,
+    (x, y): (f64, f64),
+    rebar: &[RebarEntry],
+    groups: &[LabelGroup],
+    units: Unit,
+) {
+    let mut line = 0.0;
+    for (index, entry) in rebar.iter().enumerate() {
+        let labels: Vec<String> = groups
+            .iter()
+            .filter(|g| g.entry == index)
+            .map(|g| g.label(units))
+            .collect();
+        if labels.is_empty() {
+            continue;
+        }
+
+        let content = format!("{}: {}", entry.pattern.keyword(), labels.join(" + "));
+        add_text(drawing, (x, y - line * LABEL_GAP), content);
+        line += 1.0;
+    }
+}
+
+/// Count, size, diameter and steel area of every group, and the total area.
+fn add_table(drawing: &mut Drawing, (x, y): (f64, f64), groups: &[LabelGroup], units: Unit) {
+    let mut rows = vec![[
+        "n".to_string(),
+        "Ø".to_string(),
+        format!("db ({})", units.suffix()),
+        format!("As ({}²)", units.suffix()),
+    ]];
+    let format_area = |area: f64| format_number(area / units.in_cm().powi(2));
+
+    let mut total = 0.0;
+    for label in groups {
+        let bar = &label.group.bar;
+        let area = label.bars.len() as f64 * bar.area;
+        total += area;
+        rows.push([
+            label.bars.len().to_string(),
+            label.group.size.clone(),
+            format_length(bar.diameter, units),
+            format_area(area),
+        ]);
+    }
+    rows.push([
+        String::new(),
+        String::new(),
+        "Total".to_string(),
+        format_area(total),
+    ]);
+
+    for (i, row) in rows.into_iter().enumerate() {
+        let row_y = y - i as f64 * LABEL_GAP;
+        for (cell, column) in row.into_iter().zip(TABLE_COLUMNS) {
+            if !cell.is_empty() {
+                add_text(drawing, (x + column, row_y), cell);
+            }
+        }
+    }
+}
+
+/// Count and size of a group of bars, e.g. `3Ø1"`, `4#6` or `2Ø16`.
+pub fn bar_label(count: usize, size: &str) -> String {
+    format!("{}{}", count, size_label(size))
+}
+
+/// Size with its diameter sign, e.g. `Ø1"`, `#6` or `Ø16`.
+fn size_label(size: &str) -> String {
+    if size.starts_with(['#', 'Ø', 'ø']) {
+        size.to_string()
+    } else {
+        format!("Ø{}", size)
+    }
+}
+
+/// Left-aligned label text.
+fn add_text(drawing: &mut Drawing, (x, y): (f64, f64), content: String) {
+    drawing.add(Primitive::Text {
+        x,
+        y,
+        content,
+        anchor: Some("west".to_string()),
+    });
+}
+
+fn add_dimension(
+    drawing: &mut Drawing,
+    start: (f64, f64),
+    end: (f64, f64),
+    offset: f64,
+    text: String,
+) {
+    drawing.add(Primitive::Dimension {
+        start,
+        end,
+        offset,
+        text,
+        stroke: Some(Stroke {
+            color: "black".to_string(),
+            width: 0.5,
+        }),
+        group: Some("dimension".to_string()),
+    });
+}
+
+/// Formats a length in cm in the declared units, e.g. `30` or `2.54`.
+pub fn format_length(value: f64, units: Unit) -> String {
+    format_number(value / units.in_cm())
+}
+
+/// Formats a number with at most two decimals.
+fn format_number(value: f64) -> String {
+    let rounded = (value * 100.0).round() / 100.0;
+    format!("{}", rounded)
+}
@@ -0,0 +1,180 @@
+use super::{PlacedBar, annotations, in_concrete};
+use crate::parser::ast::{RebarEntry, RebarPattern, Section, Shape, Unit};
+use crate::parser::{Severity, Warning};
+
+/// Least clear distance between parallel bars, and between layers, in cm.
+const MIN_CLEAR: f64 = 2.5;
+
+/// Reports every pair of rebar lines with bars drawn over each other, once.
+pub fn overlaps(
+    warnings: &mut Vec<Warning>,
+    section: &Section,
+    rebar: &[RebarEntry],
+    bars: &[PlacedBar],
+) {
+    let mut pairs: Vec<(usize, usize)> = Vec::new();
+    for (i, a) in bars.iter().enumerate() {
+        for b in &bars[i + 1..] {
+            if clear_distance(a, b) < -1e-6 {
+                pairs.push((a.entry.min(b.entry), a.entry.max(b.entry)));
+            }
+        }
+    }
+    pairs.sort();
+    pairs.dedup();
+
+    for (first, second) in pairs {
+        let keyword = |index: usize| rebar[index].pattern.keyword();
+        let message = if first == second {
+            format!("bars of the `{}` line overlap", keyword(first))
+        } else {
+            format!(
+                "`{}` and `{}` bars overlap",
+                keyword(first),
+                keyword(second)
+            )
+        };
+        warnings.push(warning(
+            section,
+            rebar[second].line,
+            Severity::Warning,
+            "bars-overlap",
+            message,
+        ));
+    }
+}
+
+/// Reports, once per rebar line, bars that are not wholly in the concrete:
+/// past the outline or into a void.
+pub fn outside(
+    warnings: &mut Vec<Warning>,
+    section: &Section,
+    rebar: &[RebarEntry],
+    bars: &[PlacedBar],
+    shape: &Shape,
+) {
+    let mut entries: Vec<usize> = bars
+        .iter()
+        .filter(|b| !in_concrete(shape, b))
+        .map(|b| b.entry)
+        .collect();
+    entries.dedup();
+
+    for entry in entries {
+        warnings.push(warning(
+            section,
+            rebar[entry].line,
+            Severity::Warning,
+            "bar-outside",
+            format!(
+                "a `{}` bar lies outside the concrete",
+                rebar[entry].pattern.keyword()
+            ),
+        ));
+    }
+}
+
+/// Reports, once per rebar line, the closest pair of bars that are nearer
+/// than allowed: within a line, the larger of the bar diameter, 2.5 cm and
+/// 4/3 of the maximum aggregate size; between lines, 2.5 cm. Lines with bars
+/// drawn over each other are left to `overlaps`.
+pub fn clear_spacing(
+    warnings: &mut Vec<Warning>,
+    section: &Section,
+    rebar: &[RebarEntry],
+    bars: &[PlacedBar],
+    aggregate: Option<f64>,
+    units: Unit,
+) {
+    // Per rebar line: clear distance, minimum and the other line
+    let mut closest: Vec<Option<(f64, f64, usize)>> = vec![None; rebar.len()];
+    for (i, a) in bars.iter().enumerate() {
+        for b in &bars[i + 1..] {
+            let clear = clear_distance(a, b);
+            let required = if a.entry == b.entry {
+                let by_aggregate = aggregate.map_or(0.0, |size| 4.0 / 3.0 * size);
+                a.diameter.max(b.diameter).max(MIN_CLEAR).max(by_aggregate)
+            } else {
+                MIN_CLEAR
+            };
+            if clear >= required - 1e-6 {
+                continue;
+            }
+
+            let (entry, other) = (a.entry.max(b.entry), a.entry.min(b.entry));
+            if closest[entry].is_none_or(|(c, _, _)| clear < c) {
+                closest[entry] = Some((clear, required, other));
+            }
+        }
+    }
+
+    let length = |value: f64| {
+        format!(
+            "{}{}",
+            annotations::format_length(value, units),
+            units.suffix()
+        )
+    };
+    for (entry, found) in closest.into_iter().enumerate() {
+        let Some((clear, required, other)) = found else {
+            continue;
+        };
+        if clear < -1e-6 {
+            continue;
+        }
+        let keyword = |index: usize| rebar[index].pattern.keyword();
+        let explicit = |index: usize| matches!(rebar[index].pattern, RebarPattern::Bar { .. });
+        let (code, what) = if other == entry {
+            ("clear-spacing", format!("`{}` bars", keyword(entry)))
+        } else if explicit(other) && explicit(entry) {
+            // Each `bar` line is a single bar, not a layer
+            (
+                "clear-spacing",
+                format!(
+                    "`bar` bars at lines {} and {}",
+                    rebar[other].line, rebar[entry].line
+                ),
+            )
+        } else if keyword(other) == keyword(entry) {
+            ("layer-spacing", format!("`{}` layers", keyword(entry)))
+        } else {
+            (
+                "layer-spacing",
+                format!("`{}` and `{}` bars", keyword(other), keyword(entry)),
+            )
+        };
+        warnings.push(warning(
+            section,
+            rebar[entry].line,
+            Severity::Warning,
+            code,
+            format!(
+                "{} are {} apart, less than the minimum of {}",
+                what,
+                length(clear),
+                length(required)
+            ),
+        ));
+    }
+}
+
+pub fn warning(
+    section: &Section,
+    line: usize,
+    severity: Severity,
+    code: &str,
+    message: String,
+) -> Warning {
+    Warning {
+        severity,
+        code: code.to_string(),
+        message,
+        section: section.id.clone(),
+        line,
+    }
+}
+
+/// Distance between the surfaces of two bars; negative if they overlap.
+fn clear_distance(a: &PlacedBar, b: &PlacedBar) -> f64 {
+    (a.x - b.x).hypot(a.y - b.y) - (a.diameter + b.diameter) / 2.0
+}
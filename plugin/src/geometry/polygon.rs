@@ -0,0 +1,272 @@
+use super::{Insets, PlacedBar, interior_positions, place_bar, spaced_layer};
+use crate::parser::ast::{BarGroup, RebarEntry, contains_point, edge_distance, signed_area};
+
+type Point = (f64, f64);
+
+/// Left, right, bottom and top of the bounding box of the vertices.
+pub fn bounds(points: &[Point]) -> (f64, f64, f64, f64) {
+    points.iter().fold(
+        (
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+        ),
+        |(left, right, bottom, top), &(x, y)| {
+            (left.min(x), right.max(x), bottom.min(y), top.max(y))
+        },
+    )
+}
+
+/// Moves the outline and its voids so the middle of the bounding box of the
+/// outline is at the origin, like every other shape. The outline runs
+/// counter-clockwise.
+pub fn centered(outer: &[Point], voids: &[Vec<Point>]) -> (Vec<Point>, Vec<Vec<Point>>) {
+    let (left, right, bottom, top) = bounds(outer);
+    let (mx, my) = ((left + right) / 2.0, (bottom + top) / 2.0);
+    let shift = |points: &[Point]| -> Vec<Point> {
+        points.iter().map(|&(x, y)| (x - mx, y - my)).collect()
+    };
+    (
+        counter_clockwise(shift(outer)),
+        voids.iter().map(|v| shift(v)).collect(),
+    )
+}
+
+/// Centroid of the outline less its voids, relative to the middle of the
+/// bounding box.
+pub fn centroid(outer: &[Point], voids: &[Vec<Point>]) -> Point {
+    let (outer, voids) = centered(outer, voids);
+    let (mut area, mut moment_x, mut moment_y) = (0.0, 0.0, 0.0);
+    for (points, sign) in std::iter::once((&outer, 1.0)).chain(voids.iter().map(|v| (v, -1.0))) {
+        // Area and centroid of the loop, whatever its direction
+        let loop_area = signed_area(points);
+        let (cx, cy) = loop_centroid(points, loop_area);
+        let weight = sign * loop_area.abs();
+        area += weight;
+        moment_x += weight * cx;
+        moment_y += weight * cy;
+    }
+    (moment_x / area, moment_y / area)
+}
+
+fn loop_centroid(points: &[Point], area: f64) -> Point {
+    let next = points.iter().cycle().skip(1);
+    let (sx, sy) = points
+        .iter()
+        .zip(next)
+        .fold((0.0, 0.0), |(sx, sy), (a, b)| {
+            let cross = a.0 * b.1 - b.0 * a.1;
+            (sx + (a.0 + b.0) * cross, sy + (a.1 + b.1) * cross)
+        });
+    (sx / (6.0 * area), sy / (6.0 * area))
+}
+
+/// Reverses a clockwise loop, keeping its first vertex.
+fn counter_clockwise(mut points: Vec<Point>) -> Vec<Point> {
+    if signed_area(&points) < 0.0 {
+        points[1..].reverse();
+    }
+    points
+}
+
+/// Offsets every edge of a counter-clockwise outline inwards: faces looking
+/// up by `top`, faces looking down by `bottom` and the rest by `sides`.
+/// Parallel neighbouring edges keep the vertex on the offset of the second.
+pub fn inset(points: &[Point], (top, bottom, sides): (f64, f64, f64)) -> Vec<Point> {
+    let count = points.len();
+    // A point on each offset edge and its direction
+    let edges: Vec<(Point, Point)> = (0..count)
+        .map(|i| {
+            let (a, b) = (points[i], points[(i + 1) % count]);
+            let length = ((b.0 - a.0).powi(2) + (b.1 - a.1).powi(2)).sqrt();
+            let (dx, dy) = ((b.0 - a.0) / length, (b.1 - a.1) / length);
+            // The outward normal is (dy, -dx)
+            let distance = if -dx > dy.abs() {
+                top
+            } else if dx > dy.abs() {
+                bottom
+            } else {
+                sides
+            };
+            ((a.0 - dy * distance, a.1 + dx * distance), (dx, dy))
+        })
+        .collect();
+
+    (0..count)
+        .map(|i| {
+            let (a, da) = edges[(i + count - 1) % count];
+            let (b, db) = edges[i];
+            let cross = da.0 * db.1 - da.1 * db.0;
+            if cross.abs() < 1e-9 {
+                return b;
+            }
+            let t = ((b.0 - a.0) * db.1 - (b.1 - a.1) * db.0) / cross;
+            (a.0 + t * da.0, a.1 + t * da.1)
+        })
+        .collect()
+}
+
+/// Centerline of the outer tie around a counter-clockwise outline.
+pub fn tie(outline: &[Point], insets: Insets, stirrup_size: f64) -> Vec<Point> {
+    let center = stirrup_size / 2.0;
+    inset(
+        outline,
+        (
+            insets.top - center,
+            insets.bottom - center,
+            insets.sides - center,
+        ),
+    )
+}
+
+/// Whether a circle lies within the loop, touching it at most.
+pub fn encloses(points: &[Point], center: Point, radius: f64) -> bool {
+    contains_point(points, center) && edge_distance(points, center) >= radius - 1e-9
+}
+
+/// Whether a circle stays out of the area of the loop, touching it at most.
+pub fn clear_of(points: &[Point], center: Point, radius: f64) -> bool {
+    !contains_point(points, center) && edge_distance(points, center) >= radius - 1e-9
+}
+
+/// Leftmost and rightmost points of the outline at height `y`.
+fn chord(points: &[Point], y: f64) -> Option<(f64, f64)> {
+    let next = points.iter().cycle().skip(1);
+    let xs: Vec<f64> = points
+        .iter()
+        .zip(next)
+        .flat_map(|(a, b)| {
+            if (a.1 - y).abs() < 1e-9 && (b.1 - y).abs() < 1e-9 {
+                vec![a.0, b.0]
+            } else if (a.1 - y) * (b.1 - y) <= 0.0 {
+                vec![a.0 + (y - a.1) / (b.1 - a.1) * (b.0 - a.0)]
+            } else {
+                Vec::new()
+            }
+        })
+        .collect();
+    let left = xs.iter().copied().fold(f64::INFINITY, f64::min);
+    let right = xs.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    (left <= right).then_some((left, right))
+}
+
+/// Path of the bar centers around a counter-clockwise outline, with the
+/// largest bar of the line resting on the outer tie.
+fn bar_centerline(outline: &[Point], insets: Insets, entry: &RebarEntry) -> Vec<Point> {
+    let half = entry
+        .groups
+        .iter()
+        .map(|g| g.bar.diameter / 2.0)
+        .fold(0.0, f64::max);
+    inset(
+        outline,
+        (insets.top + half, insets.bottom + half, insets.sides + half),
+    )
+}
+
+/// Places a `top` or `bot` layer of a polygon: under the highest or over the
+/// lowest point of the bar centerline, `layer_offset` further in, and spread
+/// across the outline at that height.
+pub fn draw_layer(
+    bars: &mut Vec<PlacedBar>,
+    outline: &[Point],
+    (index, entry): (usize, &RebarEntry),
+    insets: Insets,
+    layer_offset: f64,
+    is_top: bool,
+) {
+    let centerline = bar_centerline(outline, insets, entry);
+    let (_, _, bottom, top) = bounds(&centerline);
+    let y = if is_top {
+        top - layer_offset
+    } else {
+        bottom + layer_offset
+    };
+    let Some((from, to)) = chord(&centerline, y) else {
+        return;
+    };
+
+    let (sizes, xs) = match entry.spacing {
+        Some(spacing) => {
+            let half = entry.groups[0].bar.diameter / 2.0;
+            spaced_layer(entry, spacing, (from - half, to + half), 0.0, None)
+        }
+        None => {
+            let sizes: Vec<(usize, &BarGroup)> = entry.bars().collect();
+            let xs = match sizes.len() {
+                1 => vec![(from + to) / 2.0],
+                count => {
+                    let mut xs = vec![from];
+                    xs.extend(interior_positions(from, to, count - 2));
+                    xs.push(to);
+                    xs
+                }
+            };
+            (sizes, xs)
+        }
+    };
+
+    for ((group, bar_group), x) in sizes.into_iter().zip(xs) {
+        let bar_diam = bar_group.bar.diameter;
+        place_bar(bars, x, y, bar_diam, &bar_group.size, (index, group));
+    }
+}
+
+/// Places a `perim` line of a polygon. With a bar for every vertex, one goes
+/// to each vertex and the rest to the edges, each to the edge left with the
+/// longest gaps; with fewer, they are spaced evenly along the outline.
+pub fn draw_perimeter(
+    bars: &mut Vec<PlacedBar>,
+    outline: &[Point],
+    (index, entry): (usize, &RebarEntry),
+    insets: Insets,
+) {
+    let centerline = bar_centerline(outline, insets, entry);
+    let count = centerline.len();
+    let edges: Vec<(Point, Point)> = (0..count)
+        .map(|i| (centerline[i], centerline[(i + 1) % count]))
+        .collect();
+    let length = |(a, b): (Point, Point)| ((b.0 - a.0).powi(2) + (b.1 - a.1).powi(2)).sqrt();
+    let along = |(a, b): (Point, Point), t: f64| (a.0 + t * (b.0 - a.0), a.1 + t * (b.1 - a.1));
+
+    let sizes: Vec<(usize, &BarGroup)> = entry.bars().collect();
+    let mut points = Vec::new();
+    if sizes.len() >= count {
+        let mut per_edge = vec![0; count];
+        for _ in count..sizes.len() {
+            let gap = |i: usize| length(edges[i]) / (per_edge[i] + 1) as f64;
+            let longest = (0..count)
+                .max_by(|&a, &b| gap(a).total_cmp(&gap(b)))
+                .unwrap_or(0);
+            per_edge[longest] += 1;
+        }
+        for (edge, n) in edges.iter().zip(per_edge) {
+            points.push(edge.0);
+            for k in 1..=n {
+                points.push(along(*edge, k as f64 / (n as f64 + 1.0)));
+            }
+        }
+    } else {
+        let total: f64 = edges.iter().map(|&e| length(e)).sum();
+        let step = total / sizes.len() as f64;
+        let mut edges = edges.iter().peekable();
+        let mut start = 0.0;
+        for i in 0..sizes.len() {
+            let distance = i as f64 * step;
+            while let Some(&&edge) = edges.peek() {
+                if distance <= start + length(edge) {
+                    points.push(along(edge, (distance - start) / length(edge)));
+                    break;
+                }
+                start += length(edge);
+                edges.next();
+            }
+        }
+    }
+
+    for ((group, bar_group), (x, y)) in sizes.into_iter().zip(points) {
+        let bar_diam = bar_group.bar.diameter;
+        place_bar(bars, x, y, bar_diam, &bar_group.size, (index, group));
+    }
+}
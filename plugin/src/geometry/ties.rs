@@ -0,0 +1,413 @@
+use super::{Drawing, PlacedBar, Primitive, Stroke, add_arc};
+use crate::parser::ast::{Corner, Spacing, StirrupKind, StirrupsConfig, TieHook};
+use std::f64::consts::PI;
+
+/// Extension of a tie hook past its bend, in tie diameters.
+const HOOK_DIAMETERS: f64 = 6.0;
+
+/// Shortest extension of a tie hook, in cm.
+const MIN_HOOK: f64 = 7.5;
+
+/// Extra turns at each end of a spiral, for anchorage.
+pub const EXTRA_TURNS: f64 = 1.5;
+
+/// Centerline of the outer tie around a rectangular web.
+pub struct Hoop {
+    pub x: f64, // Left edge
+    pub y: f64, // Bottom edge
+    pub w: f64,
+    pub h: f64,
+    pub r: f64,    // Radius of the bends
+    pub size: f64, // Diameter of the outer tie
+}
+
+/// Draws the ties of a rectangular web: the outer tie along `hoop`, then
+/// the inner ties against its inside face, through the bars of the faces.
+/// Without ties, a closed hoop is drawn, closing at the top-left corner.
+/// Returns the inner ties left without bars to pass through.
+pub fn draw<'a>(
+    drawing: &mut Drawing,
+    ties: &'a [StirrupsConfig],
+    hoop: &Hoop,
+    bars: &[PlacedBar],
+) -> Vec<&'a StirrupsConfig> {
+    let outer = ties.iter().find(|t| t.kind.is_outer());
+    let kind = outer.map_or(StirrupKind::Closed, |t| t.kind);
+    let corner = match outer.and_then(|t| t.hook) {
+        Some(TieHook::Corner(corner)) => corner,
+        _ => Corner::default(),
+    };
+    let hook = hook_length(hoop.size);
+    match kind {
+        StirrupKind::OpenTop => add_tie(drawing, open_top(hoop), false, "stirrup"),
+        StirrupKind::Overlapping => {
+            for (x, w) in overlapping_hoops(hoop, bars) {
+                let points = hooked_rect((x, hoop.y, w, hoop.h), hoop.r, hook, corner);
+                add_tie(drawing, points, false, "stirrup");
+            }
+        }
+        _ => {
+            let outline = (hoop.x, hoop.y, hoop.w, hoop.h);
+            let points = hooked_rect(outline, hoop.r, hook, corner);
+            add_tie(drawing, points, false, "stirrup");
+        }
+    }
+
+    // Bars of each face within the outer tie, leaving out its ends
+    let inside: Vec<&PlacedBar> = bars
+        .iter()
+        .filter(|b| b.x > hoop.x && b.x < hoop.x + hoop.w && b.y > hoop.y && b.y < hoop.y + hoop.h)
+        .collect();
+    let (top_bars, bottom_bars) = (
+        face_bars(&inside, true, 1.0),
+        face_bars(&inside, true, -1.0),
+    );
+    let (left_bars, right_bars) = (
+        face_bars(&inside, false, -1.0),
+        face_bars(&inside, false, 1.0),
+    );
+
+    let mut unplaced = Vec::new();
+    for tie in ties.iter().filter(|t| !t.kind.is_outer()) {
+        let size = tie.bar.diameter;
+        let gap = (hoop.size + size) / 2.0;
+        let (left, right) = (hoop.x + gap, hoop.x + hoop.w - gap);
+        let (bottom, top) = (hoop.y + gap, hoop.y + hoop.h - gap);
+        let (cx, cy) = ((left + right) / 2.0, (bottom + top) / 2.0);
+
+        match tie.kind {
+            StirrupKind::Diamond => {
+                // A corner at the bar nearest the middle of each face
+                let corners = (
+                    nearest(&top_bars, cx),
+                    nearest(&right_bars, cy),
+                    nearest(&bottom_bars, cx),
+                    nearest(&left_bars, cy),
+                );
+                let (Some(t), Some(r), Some(b), Some(l)) = corners else {
+                    unplaced.push(tie);
+                    continue;
+                };
+                let points = vec![(t, top), (right, r), (b, bottom), (left, l)];
+                add_tie(drawing, points, true, "stirrup_inner");
+            }
+            StirrupKind::Cross => {
+                // A 135° hook at the start and a 90° one at the end, unless
+                // both are given
+                let angles = match tie.hook {
+                    Some(TieHook::Angle(angle)) => (angle, angle),
+                    _ => (135.0, 90.0),
+                };
+                let hook = (hook_length(size), angles);
+
+                // Each crosstie runs between two bars facing each other
+                let x = nearest(&shared(&top_bars, &bottom_bars), cx);
+                let y = nearest(&shared(&left_bars, &right_bars), cy);
+                if let Some(x) = x {
+                    let points = crosstie((x, top), (x, bottom), hook);
+                    add_tie(drawing, points, false, "stirrup_inner");
+                }
+                if let Some(y) = y {
+                    let points = crosstie((left, y), (right, y), hook);
+                    add_tie(drawing, points, false, "stirrup_inner");
+                }
+                if x.is_none() || y.is_none() {
+                    unplaced.push(tie);
+                }
+            }
+            _ => {}
+        }
+    }
+    unplaced
+}
+
+/// Positions along a face of the bars resting on it, leaving out the bars
+/// at its ends, which sit in the corners. A bar rests on the face when it
+/// reaches within half its diameter of the outermost bar, so the corner
+/// bars seated in the bends count. `horizontal` picks the top and bottom
+/// faces, and `side` is 1 for the top and right faces and -1 for the bottom
+/// and left ones.
+fn face_bars(bars: &[&PlacedBar], horizontal: bool, side: f64) -> Vec<f64> {
+    let across = |b: &PlacedBar| if horizontal { b.y } else { b.x };
+    let along = |b: &PlacedBar| if horizontal { b.x } else { b.y };
+    let outermost = |b: &PlacedBar| side * across(b) + b.diameter / 2.0;
+    let face = bars
+        .iter()
+        .map(|b| outermost(b))
+        .fold(f64::NEG_INFINITY, f64::max);
+    let positions: Vec<f64> = bars
+        .iter()
+        .filter(|b| outermost(b) > face - b.diameter / 2.0)
+        .map(|b| along(b))
+        .collect();
+    let first = positions.iter().copied().fold(f64::INFINITY, f64::min);
+    let last = positions.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    positions
+        .into_iter()
+        .filter(|p| *p > first + 1e-6 && *p < last - 1e-6)
+        .collect()
+}
+
+/// Positions found on both faces.
+fn shared(a: &[f64], b: &[f64]) -> Vec<f64> {
+    a.iter()
+        .copied()
+        .filter(|p| b.iter().any(|q| (p - q).abs() < 1e-6))
+        .collect()
+}
+
+/// Position nearest to `target`.
+fn nearest(positions: &[f64], target: f64) -> Option<f64> {
+    positions
+        .iter()
+        .copied()
+        .min_by(|a, b| (a - target).abs().total_cmp(&(b - target).abs()))
+}
+
+/// Closed rounded rectangle from its lower-left corner, clockwise from the
+/// top edge.
+fn rounded_rect(x: f64, y: f64, w: f64, h: f64, r: f64) -> Vec<(f64, f64)> {
+    let mut points = Vec::new();
+
+    // Top edge
+    points.push((x + r, y + h));
+    points.push((x + w - r, y + h));
+
+    // Top-right corner
+    add_arc(&mut points, x + w - r, y + h - r, r, PI / 2.0, 0.0);
+
+    // Right edge
+    points.push((x + w, y + h - r));
+    points.push((x + w, y + r));
+
+    // Bottom-right corner
+    add_arc(&mut points, x + w - r, y + r, r, 0.0, -PI / 2.0);
+
+    // Bottom edge
+    points.push((x + w - r, y));
+    points.push((x + r, y));
+
+    // Bottom-left corner
+    add_arc(&mut points, x + r, y + r, r, -PI / 2.0, -PI);
+
+    // Left edge
+    points.push((x, y + r));
+    points.push((x, y + h - r));
+
+    // Top-left corner
+    add_arc(&mut points, x + r, y + h - r, r, PI, PI / 2.0);
+
+    points
+}
+
+/// Extension of the hooks of a tie of diameter `size` past their bends.
+fn hook_length(size: f64) -> f64 {
+    (HOOK_DIAMETERS * size).max(MIN_HOOK)
+}
+
+/// Rounded rectangle that closes at `corner` with two 135° hooks of length
+/// `hook`, both pointing into the core. The path starts at the tip of the
+/// hook that ends the top or bottom edge and ends at the tip of the other.
+fn hooked_rect(
+    (x, y, w, h): (f64, f64, f64, f64),
+    r: f64,
+    hook: f64,
+    corner: Corner,
+) -> Vec<(f64, f64)> {
+    // Built closing at the top-left corner, where both hooks point down and
+    // to the right, then mirrored onto the chosen corner
+    let inward = (hook / 2.0_f64.sqrt(), -hook / 2.0_f64.sqrt());
+    let (cx, cy) = (x + r, y + h - r);
+    let start = (cx + r * (1.25 * PI).cos(), cy + r * (1.25 * PI).sin());
+
+    let mut points = vec![(start.0 + inward.0, start.1 + inward.1)];
+    add_arc(&mut points, cx, cy, r, 1.25 * PI, PI / 2.0);
+    points.extend(rounded_rect(x, y, w, h, r).into_iter().skip(1));
+    points.pop();
+    add_arc(&mut points, cx, cy, r, PI / 2.0, PI / 4.0);
+    let (end_x, end_y) = points[points.len() - 1];
+    points.push((end_x + inward.0, end_y + inward.1));
+
+    let (right, bottom) = match corner {
+        Corner::TopLeft => (false, false),
+        Corner::TopRight => (true, false),
+        Corner::BottomRight => (true, true),
+        Corner::BottomLeft => (false, true),
+    };
+    points
+        .into_iter()
+        .map(|(px, py)| {
+            (
+                if right { 2.0 * x + w - px } else { px },
+                if bottom { 2.0 * y + h - py } else { py },
+            )
+        })
+        .collect()
+}
+
+/// U stirrup: down the left leg, across the bottom and up the right leg,
+/// both legs ending at the top of the hoop.
+fn open_top(hoop: &Hoop) -> Vec<(f64, f64)> {
+    let Hoop { x, y, w, h, r, .. } = *hoop;
+    let mut points = vec![(x, y + h), (x, y + r)];
+    add_arc(&mut points, x + r, y + r, r, PI, 1.5 * PI);
+    points.push((x + r, y));
+    points.push((x + w - r, y));
+    add_arc(&mut points, x + w - r, y + r, r, -PI / 2.0, 0.0);
+    points.push((x + w, y + r));
+    points.push((x + w, y + h));
+    points
+}
+
+/// Left edge and width of two hoops that together wrap the section. Each
+/// inner leg passes outside the interior bar nearest to a third of the way
+/// across from the other face, so the bars near the middle are wrapped by
+/// both hoops.
+fn overlapping_hoops(hoop: &Hoop, bars: &[PlacedBar]) -> [(f64, f64); 2] {
+    let (left, right) = (hoop.x, hoop.x + hoop.w);
+    let center = (left + right) / 2.0;
+
+    // Bars of the faces are wrapped by the outer legs
+    let min_x = bars.iter().map(|b| b.x).fold(f64::INFINITY, f64::min);
+    let max_x = bars.iter().map(|b| b.x).fold(f64::NEG_INFINITY, f64::max);
+    let interior: Vec<&PlacedBar> = bars
+        .iter()
+        .filter(|b| b.x > min_x + 1e-6 && b.x < max_x - 1e-6)
+        .collect();
+    let nearest = |target: f64| {
+        interior
+            .iter()
+            .filter(|b| (b.x - center) * (target - center) >= -1e-6)
+            .min_by(|a, b| (a.x - target).abs().total_cmp(&(b.x - target).abs()))
+    };
+
+    let (first, second) = (left + 2.0 * hoop.w / 3.0, left + hoop.w / 3.0);
+    let first_end =
+        nearest(first).map_or(first, |bar| bar.x + bar.diameter / 2.0 + hoop.size / 2.0);
+    let second_start =
+        nearest(second).map_or(second, |bar| bar.x - bar.diameter / 2.0 - hoop.size / 2.0);
+    [
+        (left, first_end - left),
+        (second_start, right - second_start),
+    ]
+}
+
+/// Single-leg tie from `from` to `to`, with hooks of length `hook` bent by
+/// the given angles in degrees at `from` and at `to`, both turned to the
+/// same side.
+fn crosstie(
+    from: (f64, f64),
+    to: (f64, f64),
+    (hook, (start_angle, end_angle)): (f64, (f64, f64)),
+) -> Vec<(f64, f64)> {
+    let length = ((to.0 - from.0).powi(2) + (to.1 - from.1).powi(2)).sqrt();
+    let (ux, uy) = ((to.0 - from.0) / length, (to.1 - from.1) / length);
+    let rotate = |(x, y): (f64, f64), angle: f64| {
+        (
+            x * angle.cos() - y * angle.sin(),
+            x * angle.sin() + y * angle.cos(),
+        )
+    };
+    let start = rotate((-ux, -uy), start_angle.to_radians());
+    let end = rotate((ux, uy), -end_angle.to_radians());
+    vec![
+        (from.0 + hook * start.0, from.1 + hook * start.1),
+        from,
+        to,
+        (to.0 + hook * end.0, to.1 + hook * end.1),
+    ]
+}
+
+fn add_tie(drawing: &mut Drawing, points: Vec<(f64, f64)>, closed: bool, group: &str) {
+    drawing.add(Primitive::Path {
+        points,
+        closed,
+        stroke: Some(Stroke {
+            color: "#000080".to_string(), // Navy
+            width: 2.0,
+        }),
+        fill: None,
+        group: Some(group.to_string()),
+    });
+}
+
+/// Positions of the ties along the span between `from` and `to`: fixed
+/// spacings from both ends towards the middle, then the rest spread evenly
+/// at no more than its spacing.
+pub fn positions(dist: &[Spacing], from: f64, to: f64) -> Vec<f64> {
+    let mut positions = Vec::new();
+    let mut left_x = from;
+    let mut right_x = to;
+
+    for spacing in dist {
+        match *spacing {
+            Spacing::Fixed { count, dist } => {
+                for _ in 0..count {
+                    left_x += dist;
+                    if left_x >= right_x {
+                        break;
+                    }
+                    positions.push(left_x);
+
+                    right_x -= dist;
+                    if right_x <= left_x {
+                        break;
+                    }
+                    positions.push(right_x);
+                }
+            }
+            Spacing::Rest { dist } => {
+                let gap = right_x - left_x;
+                if gap > 0.0 {
+                    let num_spaces = (gap / dist).ceil() as u32;
+                    if num_spaces > 0 {
+                        let actual_dist = gap / (num_spaces as f64);
+                        for k in 1..num_spaces {
+                            positions.push(left_x + (k as f64) * actual_dist);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    positions
+}
+
+/// Longitudinal projection of a spiral and the quantities of its bar.
+pub struct Spiral {
+    pub points: Vec<(f64, f64)>,
+    pub turns: f64,  // Including the extra turns at both ends
+    pub length: f64, // Of the bar, in cm
+}
+
+/// Spiral of centerline `radius` between `from` and `to` along the member,
+/// projected as inclined lines across the hoop, half a turn each. The extra
+/// turns at each end are closed up to the bar diameter; the turns between
+/// them are spread evenly at no more than `pitch`. `None` if the extra
+/// turns leave no length for the rest.
+pub fn spiral((from, to): (f64, f64), radius: f64, pitch: f64, bar: f64) -> Option<Spiral> {
+    let (start, end) = (from + EXTRA_TURNS * bar, to - EXTRA_TURNS * bar);
+    if end - start < 1e-9 {
+        return None;
+    }
+    let turns = ((end - start) / pitch).ceil();
+    let actual = (end - start) / turns;
+
+    let extra = (2.0 * EXTRA_TURNS) as usize;
+    let stations = (0..extra)
+        .map(|i| from + i as f64 * bar / 2.0)
+        .chain((0..2 * turns as usize).map(|i| start + i as f64 * actual / 2.0))
+        .chain((0..=extra).map(|i| end + i as f64 * bar / 2.0));
+    let points = stations
+        .enumerate()
+        .map(|(i, x)| (x, if i % 2 == 0 { radius } else { -radius }))
+        .collect();
+
+    // Each turn unrolls into the hypotenuse of its circumference and pitch
+    let turn = |pitch: f64| (2.0 * PI * radius).hypot(pitch);
+    Some(Spiral {
+        points,
+        turns: turns + 2.0 * EXTRA_TURNS,
+        length: turns * turn(actual) + 2.0 * EXTRA_TURNS * turn(bar),
+    })
+}
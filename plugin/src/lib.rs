@@ -1,3 +1,4 @@
+mod catalog;
 mod geometry;
 mod parser;
 
@@ -29,28 +30,49 @@ wasm_minimal_protocol::initiate_protocol!();
 #[cfg_attr(target_arch = "wasm32", wasm_func)]
 pub fn priv_parse(expr: &[u8]) -> Result<Vec<u8>, String> {
     let expr: String = ciborium::from_reader(expr).map_err_to_string()?;
-    let sections = parser::parse(&expr).map_err_to_string()?;
-    let expr = cbor_encode(&sections).map_err_to_string()?;
+    let document = parser::parse(&expr).map_err_to_string()?;
+    let expr = cbor_encode(&document).map_err_to_string()?;
     Ok(expr)
 }
 
+/// Result of `priv_parse_and_generate`, tagged by `status` for the Typst side.
+#[derive(serde::Serialize)]
+#[serde(tag = "status", rename_all = "lowercase")]
+enum Response {
+    Ok {
+        drawings: Vec<geometry::Drawing>,
+        warnings: Vec<parser::Warning>,
+    },
+    Error {
+        error: parser::Diagnostic,
+    },
+}
+
 #[cfg_attr(target_arch = "wasm32", wasm_func)]
 pub fn priv_parse_and_generate(expr: &[u8]) -> Result<Vec<u8>, String> {
     let expr: String = ciborium::from_reader(expr).map_err_to_string()?;
-    let sections = parser::parse(&expr).map_err_to_string()?;
 
-    let drawings: Vec<geometry::Drawing> = sections
-        .iter()
-        .flat_map(|s| geometry::generate(s))
-        .collect();
+    let response = match parser::parse(&expr) {
+        Ok(document) => {
+            let mut warnings = Vec::new();
+            let drawings = document
+                .sections
+                .iter()
+                .flat_map(|s| geometry::generate(s, &document.defaults, &mut warnings))
+                .collect();
+            Response::Ok { drawings, warnings }
+        }
+        Err(error) => Response::Error { error },
+    };
 
-    let expr = cbor_encode(&drawings).map_err_to_string()?;
+    let expr = cbor_encode(&response).map_err_to_string()?;
     Ok(expr)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use parser::ast::{Cover, Shape, Spacing, Unit};
 
     #[test]
     fn test_parse_beam() {
@@ -66,9 +88,9 @@ mod tests {
         "#;
         let result = parser::parse(input);
         assert!(result.is_ok());
-        let sections = result.unwrap();
-        assert_eq!(sections.len(), 1);
-        let section = &sections[0];
+        let document = result.unwrap();
+        assert_eq!(document.sections.len(), 1);
+        let section = &document.sections[0];
         assert_eq!(section.id, "V-101");
     }
 
@@ -85,4 +107,272 @@ mod tests {
         let result = parser::parse(input);
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_parse_shapes() {
+        let input = r#"
+            beam "R":
+                R 30 60
+            beam "T":
+                T 60 60 20 30
+            beam "L":
+                L 50 50 15 25
+            beam "P":
+                shape:
+                    polygon:
+                        (-20, 0)
+                        (20, 0)
+                        (0, 30)
+                    void "hole":
+                        polygon:
+                            (-5, 5)
+                            (5, 5)
+                            (0, 150mm)
+        "#;
+        let document = parser::parse(input).unwrap();
+        let shapes: Vec<_> = document
+            .sections
+            .iter()
+            .map(|s| s.properties.shape.clone().unwrap())
+            .collect();
+        assert_eq!(
+            shapes,
+            vec![
+                Shape::Rect {
+                    width: 30.0,
+                    height: 60.0
+                },
+                Shape::Tee {
+                    width: 60.0,
+                    height: 60.0,
+                    flange_thickness: 20.0,
+                    web_thickness: 30.0
+                },
+                Shape::Ell {
+                    width: 50.0,
+                    height: 50.0,
+                    flange_thickness: 15.0,
+                    web_thickness: 25.0
+                },
+                Shape::Polygon {
+                    outer: vec![(-20.0, 0.0), (20.0, 0.0), (0.0, 30.0)],
+                    voids: vec![vec![(-5.0, 5.0), (5.0, 5.0), (0.0, 15.0)]],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_bar_groups() {
+        let input = r#"
+            beam "V-102":
+                30 x 60
+                top 1 3/4" 1 1/2" 1 3/4"
+                bot 2 3/4" 2 1"
+        "#;
+        let document = parser::parse(input).unwrap();
+        let rebar = &document.sections[0].properties.rebar;
+        assert_eq!(rebar.len(), 2);
+        assert_eq!(rebar[0].groups.len(), 3);
+        assert_eq!(rebar[1].count(), 4);
+        assert_eq!(
+            rebar[0]
+                .bars()
+                .map(|(_, g)| g.size.as_str())
+                .collect::<Vec<_>>(),
+            vec!["3/4\"", "1/2\"", "3/4\""]
+        );
+    }
+
+    #[test]
+    fn test_generate_reports_parse_error() {
+        let input = cbor_encode("beam \"V-1\":\n  30 x 60\n  top 2\n").unwrap();
+        let output = priv_parse_and_generate(&input).unwrap();
+
+        #[derive(serde::Deserialize)]
+        struct ErrorResponse {
+            status: String,
+            error: parser::Diagnostic,
+        }
+
+        let response: ErrorResponse = ciborium::from_reader(output.as_slice()).unwrap();
+        assert_eq!(response.status, "error");
+        assert_eq!(response.error.line, 3);
+        assert_eq!(
+            response.error.message,
+            "expected number, bar size or unit after `top 2`"
+        );
+    }
+
+    #[test]
+    fn test_generate_reports_warnings() {
+        let input = cbor_encode("beam \"V-1\":\n  20 x 30\n  cover 4\n  bot 8 1\"\n").unwrap();
+        let output = priv_parse_and_generate(&input).unwrap();
+
+        #[derive(serde::Deserialize)]
+        struct OkResponse {
+            status: String,
+            drawings: Vec<geometry::Drawing>,
+            warnings: Vec<parser::Warning>,
+        }
+
+        // Still drawn, with the problem reported next to the drawing
+        let response: OkResponse = ciborium::from_reader(output.as_slice()).unwrap();
+        assert_eq!(response.status, "ok");
+        assert_eq!(response.drawings.len(), 1);
+        assert_eq!(
+            response.warnings,
+            vec![parser::Warning {
+                severity: parser::Severity::Warning,
+                code: "bars-overlap".to_string(),
+                message: "bars of the `bot` line overlap".to_string(),
+                section: "V-1".to_string(),
+                line: 4,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_set_defaults() {
+        let input = r#"
+            set:
+                scale 1:25
+                cover 5
+                fc 280
+
+            beam "V-1":
+                30 x 60
+                top 2 1/2"
+
+            beam "V-2":
+                30 x 60
+                cover 3
+                bot 3 1"
+        "#;
+        let document = parser::parse(input).unwrap();
+        assert_eq!(document.defaults.cover, Some(Cover::uniform(5.0)));
+        assert_eq!(document.defaults.scale_section, Some(1.0 / 25.0));
+
+        let first = document.sections[0]
+            .properties
+            .with_defaults(&document.defaults);
+        assert_eq!(first.cover, Some(Cover::uniform(5.0)));
+        assert_eq!(first.material.fc, Some(280.0));
+        assert_eq!(first.scale_long, Some(1.0 / 25.0));
+
+        let second = document.sections[1]
+            .properties
+            .with_defaults(&document.defaults);
+        assert_eq!(second.cover, Some(Cover::uniform(3.0)));
+    }
+
+    #[test]
+    fn test_materials() {
+        let input = r#"
+            set:
+                fc C25/30
+                fy Grade 60
+
+            beam "V-1":
+                30 x 60
+                fc 4000 psi
+                Es 200GPa
+
+            beam "V-2":
+                30 x 60
+                fc 21 MPa
+                fy 4200 kgf/cm2
+        "#;
+        let document = parser::parse(input).unwrap();
+        let close = |a: Option<f64>, b: f64| (a.unwrap() - b).abs() < 0.01;
+        assert!(close(document.defaults.material.fc, 254.93));
+        assert!(close(document.defaults.material.fy, 4218.42));
+
+        let first = document.sections[0]
+            .properties
+            .with_defaults(&document.defaults);
+        assert!(close(first.material.fc, 281.23));
+        assert!(close(first.material.fy, 4218.42));
+        assert!(close(first.material.es, 2_039_432.4));
+
+        let second = &document.sections[1].properties.material;
+        assert!(close(second.fc, 214.14));
+        assert_eq!(second.fy, Some(4200.0));
+
+        let error = parser::parse("beam \"V-1\":\n  30 x 60\n  cover 4 MPa\n").unwrap_err();
+        assert_eq!(error.message, "`MPa` is not a length unit");
+        assert_eq!(error.column, 11);
+    }
+
+    #[test]
+    fn test_bar_catalog() {
+        let input = r#"
+            set:
+                catalog metric
+
+            beam "V-1":
+                30 x 60
+                top 2 Ø16 1 20mm
+                ties Ø8 rto@20
+        "#;
+        let document = parser::parse(input).unwrap();
+        let props = &document.sections[0].properties;
+        let groups = &props.rebar[0].groups;
+        assert_eq!(groups[0].bar.diameter, 1.6);
+        assert_eq!(groups[1].size, "20mm");
+        assert_eq!(groups[1].bar.area, std::f64::consts::PI);
+        assert_eq!(props.ties[0].bar.diameter, 0.8);
+
+        let document =
+            parser::parse("beam \"V-1\":\n  30 x 60\n  bot 2 1 3/8\" 1 1-3/8\"\n").unwrap();
+        let groups = &document.sections[0].properties.rebar[0].groups;
+        assert_eq!(groups[0].size, "1 3/8\"");
+        assert_eq!(groups[0].bar, groups[1].bar);
+        assert_eq!(groups[0].bar.diameter, 3.5814);
+
+        let error = parser::parse("beam \"V-1\":\n  30 x 60\n  bot 2 #99\n").unwrap_err();
+        assert_eq!(
+            error.message,
+            "unknown bar size `#99` in the imperial catalog"
+        );
+        assert_eq!(error.column, 9);
+    }
+
+    #[test]
+    fn test_units() {
+        let input = r#"
+            set:
+                units mm
+                cover 40
+
+            beam "V-1":
+                300 x 600
+                span 4.5m
+                top 2 1/2"
+                ties 3/8" 2@50 rto@8in
+        "#;
+        let document = parser::parse(input).unwrap();
+        assert_eq!(document.defaults.units, Some(Unit::Mm));
+        assert_eq!(document.defaults.cover, Some(Cover::uniform(4.0)));
+
+        let props = &document.sections[0].properties;
+        assert_eq!(
+            props.shape,
+            Some(Shape::Rect {
+                width: 30.0,
+                height: 60.0
+            })
+        );
+        assert_eq!(props.span, Some(450.0));
+        assert_eq!(
+            props.ties[0].dist,
+            vec![
+                Spacing::Fixed {
+                    count: 2,
+                    dist: 5.0
+                },
+                Spacing::Rest { dist: 20.32 },
+            ]
+        );
+    }
 }
@@ -1,9 +1,18 @@
+use crate::catalog::{Bar, Catalog};
 use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Document {
+    pub defaults: Defaults,
+    pub sections: Vec<Section>,
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Section {
     pub kind: SectionType,
     pub id: String,
+    pub line: usize, // Of the header, 1-based
     pub properties: SectionProperties,
 }
 
@@ -24,41 +33,449 @@ pub enum View {
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct SectionProperties {
     pub shape: Option<Shape>,
-    pub cover: Option<f64>, // in cm
-    pub span: Option<f64>,  // in cm
+    pub cover: Option<Cover>,
+    pub cover_line: Option<usize>,  // Of the `cover` property, 1-based
+    pub span: Option<f64>,          // in cm
+    pub aggregate: Option<f64>,     // Maximum aggregate size, in cm
+    pub layer_spacing: Option<f64>, // Clear distance between layers, in cm
     pub view: Option<View>,
     pub scale_section: Option<f64>,
     pub scale_long: Option<f64>,
-    pub concrete: Option<f64>,        // fc in kg/cm2
-    pub rebar: Vec<RebarEntry>,       // Flattened list of rebar lines
-    pub ties: Option<StirrupsConfig>, // Unified ties/stirrups
+    pub material: Material,
+    pub dims: Option<Dims>,
+    pub labels: Option<Labels>,
+    pub origin: Option<Origin>,
+    pub rebar: Vec<RebarEntry>,    // Flattened list of rebar lines
+    pub ties: Vec<StirrupsConfig>, // Lines of `ties:`, or the single `ties` line
+}
+
+impl SectionProperties {
+    /// Returns a copy of these properties with every unset value taken from `defaults`.
+    pub fn with_defaults(&self, defaults: &Defaults) -> SectionProperties {
+        let mut props = self.clone();
+        if props.cover.is_none() {
+            props.cover = defaults.cover;
+            props.cover_line = defaults.cover_line;
+        }
+        props.span = props.span.or(defaults.span);
+        props.aggregate = props.aggregate.or(defaults.aggregate);
+        props.layer_spacing = props.layer_spacing.or(defaults.layer_spacing);
+        props.view = props.view.or_else(|| defaults.view.clone());
+        props.scale_section = props.scale_section.or(defaults.scale_section);
+        props.scale_long = props.scale_long.or(defaults.scale_long);
+        props.material = props.material.or(&defaults.material);
+        props.dims = props.dims.or_else(|| defaults.dims.clone());
+        props.labels = props.labels.or_else(|| defaults.labels.clone());
+        props.origin = props.origin.or(defaults.origin);
+        props
+    }
+
+    /// The tie around the bars, which sets their inset from the faces.
+    pub fn outer_tie(&self) -> Option<&StirrupsConfig> {
+        self.ties.iter().find(|t| t.kind.is_outer())
+    }
+
+    /// Layer number and index of every line of `pattern`, innermost last. Layer
+    /// 1 rests on the stirrup. Lines without `layer` are numbered in the order
+    /// they are written: `top` lines downwards and `bot` lines upwards, so the
+    /// last `bot` line is the outermost.
+    pub fn layers(&self, pattern: &RebarPattern) -> Vec<(u32, usize)> {
+        let lines: Vec<(usize, &RebarEntry)> = self
+            .rebar
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| e.pattern == *pattern)
+            .collect();
+        let mut implicit: Vec<usize> = lines
+            .iter()
+            .filter(|(_, e)| e.layer.is_none())
+            .map(|(i, _)| *i)
+            .collect();
+        if *pattern == RebarPattern::Bottom {
+            implicit.reverse();
+        }
+
+        let mut layers: Vec<(u32, usize)> = lines
+            .iter()
+            .map(|&(i, e)| match e.layer {
+                Some(layer) => (layer, i),
+                None => (implicit.iter().position(|&j| j == i).unwrap() as u32 + 1, i),
+            })
+            .collect();
+        layers.sort();
+        layers
+    }
+}
+
+/// Values declared in the `set:` block, inherited by every section.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Defaults {
+    pub units: Option<Unit>, // Lengths are stored in cm; this is how they are written and drawn
+    pub catalog: Option<Catalog>,
+    pub cover: Option<Cover>,
+    pub cover_line: Option<usize>,  // Of the `cover` property, 1-based
+    pub span: Option<f64>,          // in cm
+    pub aggregate: Option<f64>,     // in cm
+    pub layer_spacing: Option<f64>, // in cm
+    pub view: Option<View>,
+    pub scale_section: Option<f64>,
+    pub scale_long: Option<f64>,
+    pub material: Material,
+    pub dims: Option<Dims>,
+    pub labels: Option<Labels>,
+    pub origin: Option<Origin>,
+}
+
+/// Strengths and moduli of the concrete and the reinforcement, in kgf/cm².
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Material {
+    pub fc: Option<f64>, // Concrete compressive strength
+    pub fy: Option<f64>, // Reinforcement yield strength
+    pub es: Option<f64>, // Reinforcement modulus of elasticity
+    pub ec: Option<f64>, // Concrete modulus of elasticity
+}
+
+impl Material {
+    /// Returns a copy with every unset value taken from `defaults`.
+    pub fn or(&self, defaults: &Material) -> Material {
+        Material {
+            fc: self.fc.or(defaults.fc),
+            fy: self.fy.or(defaults.fy),
+            es: self.es.or(defaults.es),
+            ec: self.ec.or(defaults.ec),
+        }
+    }
+}
+
+/// Units accepted for strengths and moduli.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum StressUnit {
+    KgfCm2,
+    Mpa,
+    Gpa,
+    Psi,
+    Ksi,
+}
+
+impl StressUnit {
+    /// Parses a unit suffix, e.g. `MPa` in `fc 21 MPa`.
+    pub fn from_suffix(suffix: &str) -> Option<StressUnit> {
+        match suffix {
+            "kgf/cm2" | "kgf/cm²" | "kg/cm2" | "kg/cm²" => Some(StressUnit::KgfCm2),
+            "MPa" => Some(StressUnit::Mpa),
+            "GPa" => Some(StressUnit::Gpa),
+            "psi" => Some(StressUnit::Psi),
+            "ksi" => Some(StressUnit::Ksi),
+            _ => None,
+        }
+    }
+
+    /// Value of one unit in kgf/cm².
+    pub fn in_kgf_cm2(self) -> f64 {
+        match self {
+            StressUnit::KgfCm2 => 1.0,
+            StressUnit::Mpa => 10.197_162,
+            StressUnit::Gpa => 10_197.162,
+            StressUnit::Psi => 0.070_307,
+            StressUnit::Ksi => 70.307,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub enum Unit {
+    Mm,
+    #[default]
+    Cm,
+    M,
+    In,
+}
+
+impl Unit {
+    /// Parses a unit keyword or suffix, e.g. `mm` in `cover 40mm`.
+    pub fn from_suffix(suffix: &str) -> Option<Unit> {
+        match suffix {
+            "mm" => Some(Unit::Mm),
+            "cm" => Some(Unit::Cm),
+            "m" => Some(Unit::M),
+            "in" => Some(Unit::In),
+            _ => None,
+        }
+    }
+
+    pub fn suffix(self) -> &'static str {
+        match self {
+            Unit::Mm => "mm",
+            Unit::Cm => "cm",
+            Unit::M => "m",
+            Unit::In => "in",
+        }
+    }
+
+    /// Length of one unit in cm.
+    pub fn in_cm(self) -> f64 {
+        match self {
+            Unit::Mm => 0.1,
+            Unit::Cm => 1.0,
+            Unit::M => 100.0,
+            Unit::In => 2.54,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum Dims {
+    Off,
+    On,   // Overall dimensions and span
+    Full, // Also cover and bar spacing
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum Labels {
+    Off,
+    Callout, // Count and size next to each bar group, with a leader line
+    Legend,  // One line per rebar line under the drawing
+    Both,    // Callout and legend
+    Table,   // Count, diameter and area of every group under the drawing
+}
+
+/// Clear cover to the ties on each face, in cm, from `cover 4` or
+/// `cover top 2.5 bot 5 sides 4`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct Cover {
+    pub top: f64,
+    pub bottom: f64,
+    pub sides: f64, // Also all around a circular section
+}
+
+impl Cover {
+    pub fn uniform(cover: f64) -> Self {
+        Cover {
+            top: cover,
+            bottom: cover,
+            sides: cover,
+        }
+    }
+
+    pub fn is_uniform(&self) -> bool {
+        self.top == self.sides && self.bottom == self.sides
+    }
+}
+
+/// Point the coordinates of `bar <size> at (x, y)` are measured from.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub enum Origin {
+    Centroid,
+    #[default]
+    BottomLeft, // Of the bounding box
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum Shape {
-    Rect { width: f64, height: f64 }, // cm
-    Circle { diameter: f64 },         // cm
+    Rect {
+        width: f64,
+        height: f64,
+    }, // cm
+    Circle {
+        diameter: f64,
+    }, // cm
+    // Flange on top across the full width, web centered below it
+    Tee {
+        width: f64,
+        height: f64,
+        flange_thickness: f64,
+        web_thickness: f64,
+    },
+    // Flange on top across the full width, web flush with the left edge
+    Ell {
+        width: f64,
+        height: f64,
+        flange_thickness: f64,
+        web_thickness: f64,
+    },
+    // Vertices as written, in either direction, and the openings through it
+    Polygon {
+        outer: Vec<(f64, f64)>,
+        voids: Vec<Vec<(f64, f64)>>,
+    },
+}
+
+/// Area enclosed by a polygon, positive when its vertices run
+/// counter-clockwise.
+pub fn signed_area(points: &[(f64, f64)]) -> f64 {
+    let next = points.iter().cycle().skip(1);
+    points
+        .iter()
+        .zip(next)
+        .map(|(a, b)| a.0 * b.1 - b.0 * a.1)
+        .sum::<f64>()
+        / 2.0
+}
+
+/// Whether a point is inside a polygon, by the crossings of a ray to its right.
+pub fn contains_point(points: &[(f64, f64)], (x, y): (f64, f64)) -> bool {
+    let next = points.iter().cycle().skip(1);
+    points
+        .iter()
+        .zip(next)
+        .filter(|(a, b)| (a.1 > y) != (b.1 > y))
+        .filter(|(a, b)| x < a.0 + (y - a.1) / (b.1 - a.1) * (b.0 - a.0))
+        .count()
+        % 2
+        == 1
+}
+
+/// Distance from a point to the nearest edge of a polygon.
+pub fn edge_distance(points: &[(f64, f64)], (x, y): (f64, f64)) -> f64 {
+    let next = points.iter().cycle().skip(1);
+    points
+        .iter()
+        .zip(next)
+        .map(|(a, b)| {
+            let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+            let t = (((x - a.0) * dx + (y - a.1) * dy) / (dx * dx + dy * dy)).clamp(0.0, 1.0);
+            (x - a.0 - t * dx).hypot(y - a.1 - t * dy)
+        })
+        .fold(f64::INFINITY, f64::min)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct RebarEntry {
     pub pattern: RebarPattern,
-    pub count: u32,
-    pub size: String, // e.g., "#3", "1/2\""
+    pub groups: Vec<BarGroup>, // In declaration order, e.g. `bot 2 3/4" 2 1"`
+    pub layer: Option<u32>,    // Declared with `layer top|bot n:`
+    pub spacing: Option<f64>,  // In cm, for `top #4 @ 20`; the count is set once laid out
+    pub line: usize,           // 1-based
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BarGroup {
+    pub count: u32,   // In a line given by spacing, the bars that fit in the section
+    pub size: String, // As written, e.g. "#3", "1/2\"", "Ø16"
+    pub bar: Bar,
+}
+
+impl RebarEntry {
+    /// Total number of bars in the line.
+    pub fn count(&self) -> u32 {
+        self.groups.iter().map(|g| g.count).sum()
+    }
+
+    /// Index and group of every bar, in the order they are placed.
+    pub fn bars(&self) -> impl Iterator<Item = (usize, &BarGroup)> {
+        self.groups
+            .iter()
+            .enumerate()
+            .flat_map(|(i, g)| std::iter::repeat_n((i, g), g.count as usize))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum RebarPattern {
     Top,
     Bottom,
+    Mid,
     Sides,
     Perimeter,
+    Corners,                // Seated in the stirrup bends of a rectangular web
+    Bar { x: f64, y: f64 }, // A single bar at a point from the origin, in cm
+}
+
+impl RebarPattern {
+    /// The keyword the line starts with, e.g. `bot`.
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            RebarPattern::Top => "top",
+            RebarPattern::Bottom => "bot",
+            RebarPattern::Mid => "mid",
+            RebarPattern::Sides => "sides",
+            RebarPattern::Perimeter => "perim",
+            RebarPattern::Corners => "corners",
+            RebarPattern::Bar { .. } => "bar",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct StirrupsConfig {
+    pub kind: StirrupKind,
     pub size: String,
+    pub bar: Bar,
     pub dist: Vec<Spacing>,
+    pub hook: Option<TieHook>, // From `hook` at the end of the line
+    pub line: usize,           // 1-based
+}
+
+/// Hooks of a tie, e.g. `hook top-right` or `hook 90`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum TieHook {
+    Corner(Corner), // Where a hoop closes with its two 135° hooks
+    Angle(f64),     // Of both ends of a crosstie, in degrees
+}
+
+impl fmt::Display for TieHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TieHook::Corner(corner) => write!(f, "hook {}", corner.keyword()),
+            TieHook::Angle(angle) => write!(f, "hook {}", angle),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub enum Corner {
+    #[default]
+    TopLeft,
+    TopRight,
+    BottomRight,
+    BottomLeft,
+}
+
+impl Corner {
+    pub fn keyword(self) -> &'static str {
+        match self {
+            Corner::TopLeft => "top-left",
+            Corner::TopRight => "top-right",
+            Corner::BottomRight => "bottom-right",
+            Corner::BottomLeft => "bottom-left",
+        }
+    }
+}
+
+/// How a tie wraps the section, e.g. `inner cross` in a `ties:` block.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum StirrupKind {
+    Closed,                // `outer`: one hoop around every bar
+    OpenTop,               // `outer open-top`: a U stirrup, e.g. for slabs
+    Overlapping, // `outer overlapping`: two hoops overlapping in the middle, for wide columns
+    Diamond,     // `inner diamond`: a hoop through the middle of each face
+    Cross,       // `inner cross`: one single-leg tie each way across the core, with hooks
+    Spiral { pitch: f64 }, // `spiral <size> pitch <p>`, in cm, for circular sections
+}
+
+impl StirrupKind {
+    /// Whether the tie goes around the bars rather than inside the outer tie.
+    pub fn is_outer(self) -> bool {
+        matches!(
+            self,
+            StirrupKind::Closed
+                | StirrupKind::OpenTop
+                | StirrupKind::Overlapping
+                | StirrupKind::Spiral { .. }
+        )
+    }
+
+    /// The words that start the line in a `ties:` block.
+    pub fn keyword(self) -> &'static str {
+        match self {
+            StirrupKind::Closed => "outer",
+            StirrupKind::OpenTop => "outer open-top",
+            StirrupKind::Overlapping => "outer overlapping",
+            StirrupKind::Diamond => "inner diamond",
+            StirrupKind::Cross => "inner cross",
+            StirrupKind::Spiral { .. } => "spiral",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -70,13 +487,23 @@ pub enum Spacing {
 // Helper enum for parsing mixed properties
 #[derive(Debug, Clone)]
 pub enum RawProperty {
-    Cover(f64),
+    Cover(Cover, usize), // With its line, 1-based
     Span(f64),
-    Concrete(f64),
+    Aggregate(f64),
+    LayerSpacing(f64),
+    Concrete(f64),        // fc
+    Steel(f64),           // fy
+    SteelModulus(f64),    // Es
+    ConcreteModulus(f64), // Ec
     Rebar(RebarEntry),
-    Ties(StirrupsConfig),
+    Ties(Vec<StirrupsConfig>),
     View(View),
     Scale(f64),
     ScaleSection(f64),
     ScaleLong(f64),
+    Dims(Dims),
+    Labels(Labels),
+    Origin(Origin),
+    Units(Unit),
+    Catalog(Catalog),
 }
@@ -0,0 +1,136 @@
+use super::lexer::{LexError, Tok};
+use super::semantic::SemanticError;
+use lalrpop_util::ParseError;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A parse or validation error located in the text the user wrote.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Diagnostic {
+    pub message: String,
+    pub line: usize,   // 1-based
+    pub column: usize, // 1-based, in characters
+    pub snippet: String,
+    pub expected: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn from_parse_error(input: &str, error: ParseError<usize, Tok<'_>, LexError>) -> Self {
+        let (pos, found, expected) = match error {
+            ParseError::InvalidToken { location } => (location, None, Vec::new()),
+            ParseError::UnrecognizedEof { location, expected } => (location, None, expected),
+            ParseError::UnrecognizedToken {
+                token: (l, t, r),
+                expected,
+            } => (l, Some((t, r)), expected),
+            ParseError::ExtraToken { token: (l, t, r) } => (l, Some((t, r)), Vec::new()),
+            ParseError::User { error } => {
+                return Self::at(input, error.location, error.message, Vec::new());
+            }
+        };
+
+        let expected: Vec<String> = expected.iter().map(|e| describe_expected(e)).collect();
+        let list = join_expected(&expected);
+        let line_start = input[..pos].rfind('\n').map_or(0, |i| i + 1);
+        let before = input[line_start..pos].trim();
+
+        let message = match found {
+            Some((Tok::Indent, _)) => "unexpected indentation".to_string(),
+            Some((Tok::Newline | Tok::Dedent, _)) | None if !expected.is_empty() => {
+                if before.is_empty() {
+                    format!("expected {}", list)
+                } else {
+                    format!("expected {} after `{}`", list, before)
+                }
+            }
+            Some((_, r)) if !expected.is_empty() => {
+                format!("unexpected `{}`, expected {}", &input[pos..r], list)
+            }
+            Some((_, r)) => format!("unexpected `{}`", &input[pos..r]),
+            None => "unexpected end of input".to_string(),
+        };
+
+        Self::at(input, pos, message, expected)
+    }
+
+    /// Points at the first character of `line`.
+    pub fn from_semantic_error(input: &str, line: usize, error: &SemanticError) -> Self {
+        let start: usize = input
+            .split_inclusive('\n')
+            .take(line - 1)
+            .map(str::len)
+            .sum();
+        let indent = input[start..].len() - input[start..].trim_start().len();
+        Self::at(input, start + indent, error.to_string(), Vec::new())
+    }
+
+    fn at(input: &str, pos: usize, message: String, expected: Vec<String>) -> Self {
+        let pos = pos.min(input.len());
+        let line_start = input[..pos].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = input[pos..].find('\n').map_or(input.len(), |i| pos + i);
+
+        Diagnostic {
+            message,
+            line: line_number(input, pos),
+            column: input[line_start..pos].chars().count() + 1,
+            snippet: input[line_start..line_end].trim_end().to_string(),
+            expected,
+        }
+    }
+}
+
+/// A problem that does not stop the drawing, e.g. overlapping bars.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Warning {
+    pub severity: Severity,
+    pub code: String, // Stable identifier, e.g. "bars-overlap"
+    pub message: String,
+    pub section: String, // Id of the section
+    pub line: usize,     // 1-based
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Warning, // The drawing is probably not what was meant
+    Info,    // A default was used in place of a missing value
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for Diagnostic {}
+
+/// 1-based line of a byte offset.
+pub fn line_number(input: &str, pos: usize) -> usize {
+    input[..pos].matches('\n').count() + 1
+}
+
+/// Turns a terminal name reported by lalrpop into something readable.
+fn describe_expected(token: &str) -> String {
+    let name = token.trim_matches('"');
+    match name {
+        "number" | "string" | "bar size" | "unit" | "end of line" => name.to_string(),
+        "indent" => "indented block".to_string(),
+        "dedent" => "end of block".to_string(),
+        _ => format!("`{}`", name),
+    }
+}
+
+/// Joins expected token descriptions as "a, b or c", dropping duplicates.
+fn join_expected(expected: &[String]) -> String {
+    let mut unique: Vec<&str> = Vec::new();
+    for e in expected {
+        if !unique.contains(&e.as_str()) {
+            unique.push(e);
+        }
+    }
+    match unique.split_last() {
+        None => String::new(),
+        Some((last, [])) => last.to_string(),
+        Some((last, rest)) => format!("{} or {}", rest.join(", "), last),
+    }
+}
@@ -0,0 +1,301 @@
+use std::collections::VecDeque;
+use std::fmt;
+
+pub type Spanned<'input> = Result<(usize, Tok<'input>, usize), LexError>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Tok<'input> {
+    Word(&'input str),    // keywords, e.g. `beam`, `top`, `x`
+    Num(f64),             // e.g. `30`, `2.5`, `-7.5`
+    Str(&'input str),     // contents of a quoted string, without quotes
+    BarSize(&'input str), // e.g. `#6`, `3/4"`, `1"`, `1-3/8"`, `Ø16`
+    Unit(&'input str),    // unit following a number, e.g. `mm` in `40mm`, `MPa` in `21 MPa`
+    Colon,
+    Slash,
+    At,
+    LParen,
+    RParen,
+    Comma,
+    Newline,
+    Indent,
+    Dedent,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LexError {
+    pub message: String,
+    pub location: usize,
+}
+
+impl fmt::Display for LexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Splits the input into tokens, emitting `Indent`/`Dedent` around indented
+/// blocks and `Newline` at the end of every non-empty line.
+pub struct Lexer<'input> {
+    input: &'input str,
+    offset: usize,       // start of the next unread line
+    indents: Vec<usize>, // empty until the first line sets the base indentation
+    pending: VecDeque<Spanned<'input>>,
+    done: bool,
+}
+
+impl<'input> Lexer<'input> {
+    pub fn new(input: &'input str) -> Self {
+        Lexer {
+            input,
+            offset: 0,
+            indents: Vec::new(),
+            pending: VecDeque::new(),
+            done: false,
+        }
+    }
+
+    /// Tokenizes the next meaningful line into `pending`.
+    /// Returns false once the input is exhausted.
+    fn lex_line(&mut self) -> bool {
+        while self.offset < self.input.len() {
+            let start = self.offset;
+            let rest = &self.input[start..];
+            let line = rest.split('\n').next().unwrap_or("");
+            self.offset = start + line.len() + 1;
+
+            let line = line.trim_end_matches('\r');
+            let trimmed = line.trim_start();
+            if trimmed.is_empty() || trimmed.starts_with("//") || trimmed.starts_with('#') {
+                continue;
+            }
+
+            let indent = line.len() - trimmed.len();
+            if let Err(e) = self.indentation(start, indent) {
+                self.pending.push_back(Err(e));
+                return true;
+            }
+            self.tokens(start, indent, line);
+            return true;
+        }
+        false
+    }
+
+    fn indentation(&mut self, start: usize, indent: usize) -> Result<(), LexError> {
+        let location = start + indent;
+        let Some(&current) = self.indents.last() else {
+            // The whole document may be indented, e.g. inside a Typst raw block
+            self.indents.push(indent);
+            return Ok(());
+        };
+
+        if indent > current {
+            self.indents.push(indent);
+            self.pending
+                .push_back(Ok((location, Tok::Indent, location)));
+            return Ok(());
+        }
+
+        while self.indents.len() > 1 && indent < self.indents[self.indents.len() - 1] {
+            self.indents.pop();
+            self.pending
+                .push_back(Ok((location, Tok::Dedent, location)));
+        }
+
+        if Some(&indent) != self.indents.last() {
+            return Err(LexError {
+                message: "indentation does not match any enclosing block".to_string(),
+                location,
+            });
+        }
+        Ok(())
+    }
+
+    fn tokens(&mut self, start: usize, indent: usize, line: &'input str) {
+        let bytes = line.as_bytes();
+        let mut i = indent;
+        let mut end = indent;
+
+        while i < line.len() {
+            let c = line[i..].chars().next().unwrap_or(' ');
+            let begin = i;
+
+            let tok = if c.is_whitespace() {
+                i += c.len_utf8();
+                continue;
+            } else if line[i..].starts_with("//") {
+                break;
+            } else if c == ':' {
+                i += 1;
+                Tok::Colon
+            } else if c == '@' {
+                i += 1;
+                Tok::At
+            } else if c == '/' {
+                i += 1;
+                Tok::Slash
+            } else if c == '(' {
+                i += 1;
+                Tok::LParen
+            } else if c == ')' {
+                i += 1;
+                Tok::RParen
+            } else if c == ',' {
+                i += 1;
+                Tok::Comma
+            } else if c == '"' {
+                match line[i + 1..].find('"') {
+                    Some(len) => {
+                        i += len + 2;
+                        Tok::Str(&line[begin + 1..i - 1])
+                    }
+                    None => {
+                        self.pending.push_back(Err(LexError {
+                            message: "unterminated string".to_string(),
+                            location: start + begin,
+                        }));
+                        return;
+                    }
+                }
+            } else if c == '#' && bytes.get(i + 1).is_some_and(u8::is_ascii_digit) {
+                i = digits(bytes, i + 1);
+                Tok::BarSize(&line[begin..i])
+            } else if (c == '-' || c == '+') && bytes.get(i + 1).is_some_and(u8::is_ascii_digit) {
+                // Signed numbers are only coordinates, never bar sizes
+                i = number_end(bytes, i + 1);
+                Tok::Num(line[begin..i].parse().unwrap_or_default())
+            } else if c.is_ascii_digit() {
+                i = digits(bytes, i);
+                if bytes.get(i) == Some(&b'.') && bytes.get(i + 1).is_some_and(u8::is_ascii_digit) {
+                    i = digits(bytes, i + 1);
+                    Tok::Num(line[begin..i].parse().unwrap_or_default())
+                } else if bytes.get(i) == Some(&b'"') {
+                    i += 1;
+                    Tok::BarSize(&line[begin..i])
+                } else if let Some(after) = fraction_end(bytes, i) {
+                    i = after;
+                    Tok::BarSize(&line[begin..i])
+                } else if bytes.get(i) == Some(&b'-')
+                    && bytes.get(i + 1).is_some_and(u8::is_ascii_digit)
+                    && let Some(after) = fraction_end(bytes, digits(bytes, i + 1))
+                {
+                    // Mixed fraction, e.g. `1-3/8"`
+                    i = after;
+                    Tok::BarSize(&line[begin..i])
+                } else {
+                    Tok::Num(line[begin..i].parse().unwrap_or_default())
+                }
+            } else if (c == 'Ø' || c == 'ø')
+                && bytes.get(i + c.len_utf8()).is_some_and(u8::is_ascii_digit)
+            {
+                i = digits(bytes, i + c.len_utf8());
+                Tok::BarSize(&line[begin..i])
+            } else if c.is_alphabetic() {
+                i += c.len_utf8();
+                while let Some(next) = line[i..].chars().next() {
+                    let hyphenated = next == '-'
+                        && line[i + 1..]
+                            .chars()
+                            .next()
+                            .is_some_and(char::is_alphabetic);
+                    if next.is_alphabetic() || next == '_' || hyphenated {
+                        i += next.len_utf8();
+                    } else {
+                        break;
+                    }
+                }
+                Tok::Word(&line[begin..i])
+            } else {
+                self.pending.push_back(Err(LexError {
+                    message: format!("unexpected character `{}`", c),
+                    location: start + begin,
+                }));
+                return;
+            };
+
+            let is_num = matches!(tok, Tok::Num(_));
+            self.pending.push_back(Ok((start + begin, tok, start + i)));
+            end = i;
+
+            // The unit may be written right after the number or after spaces
+            let rest = &line[i..];
+            let gap = rest.len() - rest.trim_start().len();
+            if is_num && let Some(unit) = unit_suffix(&rest[gap..]) {
+                let at = i + gap;
+                self.pending
+                    .push_back(Ok((start + at, Tok::Unit(unit), start + at + unit.len())));
+                i = at + unit.len();
+                end = i;
+            }
+        }
+
+        self.pending
+            .push_back(Ok((start + end, Tok::Newline, start + end)));
+    }
+}
+
+/// Unit suffixes, longest first so that `mm` is not read as `m`.
+const UNITS: [&str; 12] = [
+    "kgf/cm²", "kgf/cm2", "kg/cm²", "kg/cm2", "MPa", "GPa", "psi", "ksi", "mm", "cm", "in", "m",
+];
+
+/// The unit at the start of `rest`, if it is not part of a longer word.
+/// Lengths and stresses share the token; the grammar rejects the wrong kind.
+fn unit_suffix(rest: &str) -> Option<&str> {
+    UNITS.into_iter().find_map(|unit| {
+        let after = rest.strip_prefix(unit)?;
+        match after.chars().next() {
+            Some(c) if c.is_alphanumeric() || c == '_' => None,
+            _ => Some(&rest[..unit.len()]),
+        }
+    })
+}
+
+/// End of a fraction of an inch such as `/8"` starting at `i`.
+fn fraction_end(bytes: &[u8], i: usize) -> Option<usize> {
+    if bytes.get(i) != Some(&b'/') || !bytes.get(i + 1).is_some_and(u8::is_ascii_digit) {
+        return None;
+    }
+    let end = digits(bytes, i + 1);
+    (bytes.get(end) == Some(&b'"')).then_some(end + 1)
+}
+
+/// End of a number such as `30` or `2.5` starting at `i`.
+fn number_end(bytes: &[u8], i: usize) -> usize {
+    let i = digits(bytes, i);
+    if bytes.get(i) == Some(&b'.') && bytes.get(i + 1).is_some_and(u8::is_ascii_digit) {
+        digits(bytes, i + 1)
+    } else {
+        i
+    }
+}
+
+fn digits(bytes: &[u8], mut i: usize) -> usize {
+    while bytes.get(i).is_some_and(u8::is_ascii_digit) {
+        i += 1;
+    }
+    i
+}
+
+impl<'input> Iterator for Lexer<'input> {
+    type Item = Spanned<'input>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(token) = self.pending.pop_front() {
+                return Some(token);
+            }
+            if self.done {
+                return None;
+            }
+            if !self.lex_line() {
+                // Close every block still open at the end of the input
+                self.done = true;
+                let end = self.input.trim_end().len();
+                while self.indents.len() > 1 {
+                    self.indents.pop();
+                    self.pending.push_back(Ok((end, Tok::Dedent, end)));
+                }
+            }
+        }
+    }
+}
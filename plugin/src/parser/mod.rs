@@ -1,5 +1,13 @@
 pub mod ast;
+mod diagnostic;
+mod lexer;
+mod semantic;
+
+use crate::catalog::Catalog;
+use crate::geometry;
+pub use diagnostic::{Diagnostic, Severity, Warning};
 use lalrpop_util::lalrpop_mod;
+use lexer::{Lexer, Tok};
 
 lalrpop_mod!(
     #[allow(clippy::all)]
@@ -7,46 +15,63 @@ lalrpop_mod!(
     "/parser/grammar.rs"
 );
 
-pub fn parse(input: &str) -> Result<Vec<ast::Section>, String> {
-    let preprocessed = preprocess(input);
-    grammar::SectionsParser::new()
-        .parse(&preprocessed)
-        .map_err(|e| e.to_string())
-}
-
-fn preprocess(input: &str) -> String {
-    let mut output = String::new();
-    let mut lines = input.lines();
-    let mut current_indent = 0;
-
-    while let Some(line) = lines.next() {
-        let trimmed = line.trim();
-        if trimmed.is_empty() || trimmed.starts_with("//") || trimmed.starts_with("#") {
-            continue;
-        }
-
-        let indent = line.chars().take_while(|c| c.is_whitespace()).count();
+pub fn parse(input: &str) -> Result<ast::Document, Diagnostic> {
+    let (units, catalog) = declared_settings(input)?;
+    let mut document = grammar::DocumentParser::new()
+        .parse(input, units, catalog, Lexer::new(input))
+        .map_err(|e| Diagnostic::from_parse_error(input, e))?;
+    semantic::validate(&document)
+        .map_err(|(line, e)| Diagnostic::from_semantic_error(input, line, &e))?;
 
-        // Heuristic: Section headers end with ":"
-        if trimmed.ends_with(":") {
-            if current_indent > 0 {
-                output.push_str("}\n");
-                current_indent = 0;
+    // Lines given by spacing take the number of bars that fit in the section
+    for section in &mut document.sections {
+        let counts = geometry::bar_counts(section, &document.defaults);
+        for (entry, count) in section.properties.rebar.iter_mut().zip(counts) {
+            if entry.spacing.is_some() {
+                entry.groups[0].count = count;
             }
-            output.push_str(trimmed);
-            output.push_str(" {\n");
-            current_indent = 4; // Assume indentation
-        } else {
-            output.push_str(trimmed);
-            output.push_str("\n");
         }
     }
+    Ok(document)
+}
+
+/// Settings that change how the rest of the document is read: the units of
+/// lengths written without a suffix (`units <unit>`) and the bar catalog
+/// (`catalog <name>`). The `set:` block that opens the document is parsed on
+/// its own first; the document is then parsed again with its settings.
+fn declared_settings(input: &str) -> Result<(ast::Unit, Catalog), Diagnostic> {
+    let (units, catalog) = (ast::Unit::default(), Catalog::default());
 
-    if current_indent > 0 {
-        output.push_str("}\n");
+    let mut tokens = Lexer::new(input).peekable();
+    if !matches!(tokens.peek(), Some(Ok((_, Tok::Word("set"), _)))) {
+        return Ok((units, catalog));
     }
 
-    output
+    // Tokens up to the end of the block
+    let mut depth = 0;
+    let mut ended = false;
+    let block = tokens.take_while(|token| {
+        if ended {
+            return false;
+        }
+        match token {
+            Ok((_, Tok::Indent, _)) => depth += 1,
+            Ok((_, Tok::Dedent, _)) => {
+                depth -= 1;
+                ended = depth == 0;
+            }
+            _ => {}
+        }
+        true
+    });
+
+    let defaults = grammar::SettingsParser::new()
+        .parse(input, units, catalog, block)
+        .map_err(|e| Diagnostic::from_parse_error(input, e))?;
+    Ok((
+        defaults.units.unwrap_or(units),
+        defaults.catalog.unwrap_or(catalog),
+    ))
 }
 
 #[cfg(test)]
@@ -54,13 +79,183 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_preprocess() {
+    fn test_lexer_blocks() {
+        use lexer::Tok;
+
         let input = r#"
+beam "V-1":
+    30 x 60  // rectangular
+    top 2 #6
+"#;
+        let tokens: Vec<Tok> = Lexer::new(input).map(|t| t.unwrap().1).collect();
+        assert_eq!(
+            tokens,
+            vec![
+                Tok::Word("beam"),
+                Tok::Str("V-1"),
+                Tok::Colon,
+                Tok::Newline,
+                Tok::Indent,
+                Tok::Num(30.0),
+                Tok::Word("x"),
+                Tok::Num(60.0),
+                Tok::Newline,
+                Tok::Word("top"),
+                Tok::Num(2.0),
+                Tok::BarSize("#6"),
+                Tok::Newline,
+                Tok::Dedent,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lexer_nested_dedents() {
+        use lexer::Tok;
+
+        let input = "a:\n  b:\n    c\nd\n";
+        let tokens: Vec<Tok> = Lexer::new(input).map(|t| t.unwrap().1).collect();
+        let structure: Vec<&Tok> = tokens
+            .iter()
+            .filter(|t| matches!(t, Tok::Indent | Tok::Dedent))
+            .collect();
+        assert_eq!(
+            structure,
+            vec![&Tok::Indent, &Tok::Indent, &Tok::Dedent, &Tok::Dedent]
+        );
+
+        let error = Lexer::new("a:\n    b\n  c\n").find_map(Result::err);
+        assert_eq!(error.map(|e| e.location), Some(11));
+    }
+
+    #[test]
+    fn test_lexer_bar_sizes() {
+        let tokens: Vec<Tok> = Lexer::new("Ø16 1-3/8\" 1 3/8\" #6")
+            .map(|t| t.unwrap().1)
+            .collect();
+        assert_eq!(
+            tokens,
+            vec![
+                Tok::BarSize("Ø16"),
+                Tok::BarSize("1-3/8\""),
+                Tok::Num(1.0),
+                Tok::BarSize("3/8\""),
+                Tok::BarSize("#6"),
+                Tok::Newline,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lexer_unit_suffix() {
+        let tokens: Vec<Tok> = Lexer::new("cover 40mm 4.5m 30x60 5 m")
+            .map(|t| t.unwrap().1)
+            .collect();
+        assert_eq!(
+            tokens,
+            vec![
+                Tok::Word("cover"),
+                Tok::Num(40.0),
+                Tok::Unit("mm"),
+                Tok::Num(4.5),
+                Tok::Unit("m"),
+                Tok::Num(30.0),
+                Tok::Word("x"),
+                Tok::Num(60.0),
+                Tok::Num(5.0),
+                Tok::Unit("m"),
+                Tok::Newline,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lexer_coordinates() {
+        let tokens: Vec<Tok> = Lexer::new("bar 1\" at (-7.5, +5)")
+            .map(|t| t.unwrap().1)
+            .collect();
+        assert_eq!(
+            tokens,
+            vec![
+                Tok::Word("bar"),
+                Tok::BarSize("1\""),
+                Tok::Word("at"),
+                Tok::LParen,
+                Tok::Num(-7.5),
+                Tok::Comma,
+                Tok::Num(5.0),
+                Tok::RParen,
+                Tok::Newline,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diagnostic_points_at_original_line() {
+        let input = r#"
+// Vigas del eje A
+
 beam "V-1":
     30 x 60
+    cover 4
     top 2 #6
+    bot 3
 "#;
-        let expected = "beam \"V-1\": {\n30 x 60\ntop 2 #6\n}\n";
-        assert_eq!(preprocess(input), expected);
+        let error = parse(input).unwrap_err();
+        assert_eq!(error.line, 8);
+        assert_eq!(error.column, 10);
+        assert_eq!(error.snippet, "    bot 3");
+        assert_eq!(
+            error.message,
+            "expected number, bar size or unit after `bot 3`"
+        );
+        assert_eq!(
+            error.to_string(),
+            "line 8: expected number, bar size or unit after `bot 3`"
+        );
+    }
+
+    #[test]
+    fn test_diagnostic_unexpected_token() {
+        let input = "beam \"V-1\":\n  30 x 60\n  cover x\n";
+        let error = parse(input).unwrap_err();
+        assert_eq!(error.line, 3);
+        assert_eq!(error.column, 9);
+        assert_eq!(
+            error.message,
+            "unexpected `x`, expected `top`, `bot`, `sides` or number"
+        );
+    }
+
+    #[test]
+    fn test_settings_in_set_block() {
+        // Read before the rest of the block, whatever their order
+        let input = "set:\n  cover 40\n  units mm\n\nbeam \"V-1\":\n  300 x 600\n";
+        let document = parse(input).unwrap();
+        assert_eq!(document.defaults.cover, Some(ast::Cover::uniform(4.0)));
+
+        let error = parse("set:\n  catalog metrik\n\nbeam \"V-1\":\n  30 x 60\n").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 11));
+        assert!(error.message.starts_with("unexpected `metrik`"));
+
+        let error = parse("set:\n  units dm\n").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 9));
+
+        // Only the `set:` block declares them
+        let error = parse("beam \"V-1\":\n  30 x 60\n  units mm\n").unwrap_err();
+        assert_eq!((error.line, error.column), (3, 3));
+        assert!(error.message.starts_with("unexpected `units`"));
+    }
+
+    #[test]
+    fn test_diagnostic_inconsistent_indent() {
+        let input = "beam \"V-1\":\n    30 x 60\n  cover 4\n";
+        let error = parse(input).unwrap_err();
+        assert_eq!(error.line, 3);
+        assert_eq!(error.column, 3);
+        assert_eq!(
+            error.message,
+            "indentation does not match any enclosing block"
+        );
     }
 }
@@ -0,0 +1,644 @@
+use super::ast::{
+    Document, RebarPattern, Section, Shape, Spacing, StirrupKind, TieHook, Unit, contains_point,
+    edge_distance, signed_area,
+};
+use super::lexer::{LexError, Tok};
+use crate::catalog::Catalog;
+use lalrpop_util::ParseError;
+use std::fmt;
+
+/// A value that parses but cannot describe a real section. Lengths are in
+/// the declared units.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SemanticError {
+    UnknownBarSize {
+        size: String,
+        catalog: Catalog,
+    },
+    FractionalCount(f64),
+    ZeroCount,
+    NonPositiveDimension {
+        name: &'static str,
+        value: f64,
+    },
+    CoverTooLarge {
+        cover: f64,
+        limit: f64,
+    },
+    FaceCoversTooLarge {
+        top: f64,
+        bottom: f64,
+        height: f64,
+    },
+    MissingCover(&'static str),
+    PolygonVertices {
+        name: &'static str,
+        count: usize,
+    },
+    PolygonArea(&'static str),
+    RepeatedVertex {
+        closing: bool,
+    },
+    VoidOutside,
+    CircleCover,
+    LayerNumber {
+        pattern: &'static str,
+        expected: u32,
+        found: u32,
+    },
+    CornerCount(u32),
+    SpacedPattern(&'static str),
+    OuterTieCount(usize),
+    HookNotApplicable {
+        hook: TieHook,
+        kind: &'static str,
+    },
+    HookAngle(f64),
+}
+
+impl SemanticError {
+    /// Reports the error from a grammar action, at byte offset `location`.
+    pub fn at<'a>(self, location: usize) -> ParseError<usize, Tok<'a>, LexError> {
+        ParseError::User {
+            error: LexError {
+                message: self.to_string(),
+                location,
+            },
+        }
+    }
+}
+
+/// Vertices of a polygon as written, each with its byte offset. A vertex that
+/// repeats the one before it, or a last vertex that repeats the first, would
+/// leave an edge with no length.
+pub fn polygon_vertices(
+    vertices: Vec<(usize, (f64, f64))>,
+) -> Result<Vec<(f64, f64)>, (usize, SemanticError)> {
+    let same = |a: (f64, f64), b: (f64, f64)| (a.0 - b.0).hypot(a.1 - b.1) < 1e-9;
+    for pair in vertices.windows(2) {
+        if same(pair[0].1, pair[1].1) {
+            return Err((pair[1].0, SemanticError::RepeatedVertex { closing: false }));
+        }
+    }
+    if let [(_, first), .., (location, last)] = vertices[..]
+        && same(first, last)
+    {
+        return Err((location, SemanticError::RepeatedVertex { closing: true }));
+    }
+    Ok(vertices.into_iter().map(|(_, point)| point).collect())
+}
+
+/// Whether every vertex of a void, and the middle of every edge, is inside the
+/// outline or on it.
+pub fn void_inside(outer: &[(f64, f64)], void: &[(f64, f64)]) -> bool {
+    let next = void.iter().cycle().skip(1);
+    let middles = void
+        .iter()
+        .zip(next)
+        .map(|(a, b)| ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0));
+    void.iter()
+        .copied()
+        .chain(middles)
+        .all(|p| contains_point(outer, p) || edge_distance(outer, p) < 1e-9)
+}
+
+impl fmt::Display for SemanticError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SemanticError::UnknownBarSize { size, catalog } => {
+                write!(
+                    f,
+                    "unknown bar size `{}` in the {} catalog",
+                    size,
+                    catalog.name()
+                )
+            }
+            SemanticError::FractionalCount(n) => {
+                write!(f, "expected a whole number of bars, found {}", n)
+            }
+            SemanticError::ZeroCount => write!(f, "expected at least one bar, found 0"),
+            SemanticError::NonPositiveDimension { name, value } => {
+                write!(f, "{} must be greater than zero, found {}", name, value)
+            }
+            SemanticError::CoverTooLarge { cover, limit } => write!(
+                f,
+                "cover {} leaves no room for bars, half the section is {}",
+                cover, limit
+            ),
+            SemanticError::FaceCoversTooLarge {
+                top,
+                bottom,
+                height,
+            } => write!(
+                f,
+                "cover top {} bot {} leaves no room for bars, the section is {} high",
+                top, bottom, height
+            ),
+            SemanticError::MissingCover(face) => write!(
+                f,
+                "no cover for `{}`; give every face, or a value for the rest first",
+                face
+            ),
+            SemanticError::PolygonVertices { name, count } => {
+                write!(f, "a {} needs at least 3 vertices, found {}", name, count)
+            }
+            SemanticError::PolygonArea(name) => write!(f, "the {} encloses no area", name),
+            SemanticError::RepeatedVertex { closing: false } => {
+                write!(f, "the vertex repeats the one before it")
+            }
+            SemanticError::RepeatedVertex { closing: true } => write!(
+                f,
+                "the last vertex repeats the first; a polygon closes on its own"
+            ),
+            SemanticError::VoidOutside => write!(f, "the void is not inside the polygon"),
+            SemanticError::CircleCover => {
+                write!(f, "a circular section takes a single `cover` value")
+            }
+            SemanticError::CornerCount(n) => write!(f, "`corners` takes 4 bars, found {}", n),
+            SemanticError::OuterTieCount(0) => {
+                write!(f, "`inner` ties need an `outer` tie around them")
+            }
+            SemanticError::OuterTieCount(n) => {
+                write!(f, "expected one `outer` tie, found {}", n)
+            }
+            SemanticError::HookNotApplicable { hook, kind } => {
+                write!(f, "`{}` does not apply to `{}` ties", hook, kind)
+            }
+            SemanticError::HookAngle(angle) => {
+                write!(f, "expected a hook of 90 or 135 degrees, found {}", angle)
+            }
+            SemanticError::SpacedPattern(pattern) => write!(
+                f,
+                "`{}` bars cannot be given by spacing, only `top`, `bot` and `mid`",
+                pattern
+            ),
+            SemanticError::LayerNumber {
+                pattern,
+                expected,
+                found,
+            } => write!(
+                f,
+                "expected `{}` layer {}, found layer {}; layers are numbered from 1 at the stirrup",
+                pattern, expected, found
+            ),
+        }
+    }
+}
+
+/// Checks the values the grammar cannot check on its own. Returns the first
+/// error with the line it belongs to.
+pub fn validate(document: &Document) -> Result<(), (usize, SemanticError)> {
+    let units = document.defaults.units.unwrap_or_default();
+    for section in &document.sections {
+        validate_section(section, document, units)?;
+    }
+    Ok(())
+}
+
+fn validate_section(
+    section: &Section,
+    document: &Document,
+    units: Unit,
+) -> Result<(), (usize, SemanticError)> {
+    let props = section.properties.with_defaults(&document.defaults);
+    let positive = |name, value: f64, line| {
+        if value > 0.0 {
+            Ok(())
+        } else {
+            let value = value / units.in_cm();
+            Err((line, SemanticError::NonPositiveDimension { name, value }))
+        }
+    };
+
+    // Width and height of the region the ties enclose
+    let mut core = (f64::INFINITY, f64::INFINITY);
+    if let Some(shape) = &props.shape {
+        let dimensions = match shape {
+            Shape::Rect { width, height } => vec![("width", *width), ("height", *height)],
+            Shape::Circle { diameter } => vec![("diameter", *diameter)],
+            Shape::Polygon { outer, voids } => {
+                let loops = std::iter::once(("polygon", outer))
+                    .chain(voids.iter().map(|void| ("void", void)));
+                for (name, points) in loops {
+                    if points.len() < 3 {
+                        let count = points.len();
+                        let error = SemanticError::PolygonVertices { name, count };
+                        return Err((section.line, error));
+                    }
+                    if signed_area(points).abs() < 1e-9 {
+                        return Err((section.line, SemanticError::PolygonArea(name)));
+                    }
+                }
+                Vec::new()
+            }
+            Shape::Tee {
+                width,
+                height,
+                flange_thickness,
+                web_thickness,
+            }
+            | Shape::Ell {
+                width,
+                height,
+                flange_thickness,
+                web_thickness,
+            } => vec![
+                ("width", *width),
+                ("height", *height),
+                ("flange thickness", *flange_thickness),
+                ("web thickness", *web_thickness),
+            ],
+        };
+        for (name, value) in dimensions {
+            positive(name, value, section.line)?;
+        }
+        // Bars sit in the web, so a flange does not make room for a thicker cover
+        core = match shape {
+            Shape::Rect { width, height } => (*width, *height),
+            Shape::Circle { diameter } => (*diameter, *diameter),
+            Shape::Tee {
+                height,
+                web_thickness,
+                ..
+            }
+            | Shape::Ell {
+                height,
+                web_thickness,
+                ..
+            } => (*web_thickness, *height),
+            // Measured on the bounding box
+            Shape::Polygon { outer, .. } => {
+                let extent = |axis: fn(&(f64, f64)) -> f64| {
+                    let values = outer.iter().map(axis);
+                    values.clone().fold(f64::MIN, f64::max) - values.fold(f64::MAX, f64::min)
+                };
+                (extent(|p| p.0), extent(|p| p.1))
+            }
+        };
+    }
+
+    if let Some(span) = props.span {
+        positive("span", span, section.line)?;
+    }
+    if let Some(aggregate) = props.aggregate {
+        positive("aggregate", aggregate, section.line)?;
+    }
+    if let Some(spacing) = props.layer_spacing {
+        positive("layer spacing", spacing, section.line)?;
+    }
+    if let Some(cover) = props.cover {
+        // At the `cover` line, in the section or in `set:`
+        let line = props.cover_line.unwrap_or(section.line);
+        for value in [cover.top, cover.bottom, cover.sides] {
+            positive("cover", value, line)?;
+        }
+        if let Some(Shape::Circle { .. }) = props.shape
+            && !cover.is_uniform()
+        {
+            return Err((line, SemanticError::CircleCover));
+        }
+
+        // The same cover on opposite faces is compared with half the section
+        let (width, height) = core;
+        let error = if 2.0 * cover.sides >= width {
+            Some(SemanticError::CoverTooLarge {
+                cover: cover.sides / units.in_cm(),
+                limit: width / 2.0 / units.in_cm(),
+            })
+        } else if cover.top == cover.bottom && 2.0 * cover.top >= height {
+            Some(SemanticError::CoverTooLarge {
+                cover: cover.top / units.in_cm(),
+                limit: height / 2.0 / units.in_cm(),
+            })
+        } else if cover.top + cover.bottom >= height {
+            Some(SemanticError::FaceCoversTooLarge {
+                top: cover.top / units.in_cm(),
+                bottom: cover.bottom / units.in_cm(),
+                height: height / units.in_cm(),
+            })
+        } else {
+            None
+        };
+        if let Some(error) = error {
+            return Err((line, error));
+        }
+    }
+
+    for entry in &props.rebar {
+        if let Some(spacing) = entry.spacing {
+            if !matches!(
+                entry.pattern,
+                RebarPattern::Top | RebarPattern::Bottom | RebarPattern::Mid
+            ) {
+                let error = SemanticError::SpacedPattern(entry.pattern.keyword());
+                return Err((entry.line, error));
+            }
+            positive("spacing", spacing, entry.line)?;
+            continue;
+        }
+        if entry.groups.iter().any(|g| g.count == 0) {
+            return Err((entry.line, SemanticError::ZeroCount));
+        }
+        if entry.pattern == RebarPattern::Corners && entry.count() != 4 {
+            return Err((entry.line, SemanticError::CornerCount(entry.count())));
+        }
+    }
+
+    // Layers must follow each other without gaps or repeats
+    for pattern in [RebarPattern::Top, RebarPattern::Bottom] {
+        for (expected, (found, index)) in (1..).zip(props.layers(&pattern)) {
+            if found != expected {
+                let error = SemanticError::LayerNumber {
+                    pattern: pattern.keyword(),
+                    expected,
+                    found,
+                };
+                return Err((props.rebar[index].line, error));
+            }
+        }
+    }
+
+    // Inner ties sit inside the one tie around the bars
+    let outer: Vec<usize> = props
+        .ties
+        .iter()
+        .filter(|t| t.kind.is_outer())
+        .map(|t| t.line)
+        .collect();
+    match (outer.as_slice(), props.ties.first()) {
+        ([], Some(tie)) => return Err((tie.line, SemanticError::OuterTieCount(0))),
+        ([_, second, ..], _) => {
+            return Err((*second, SemanticError::OuterTieCount(outer.len())));
+        }
+        _ => {}
+    }
+
+    for ties in &props.ties {
+        if let StirrupKind::Spiral { pitch } = ties.kind {
+            positive("pitch", pitch, ties.line)?;
+        }
+        // Hoops close at a corner; crossties take the angle of their hooks
+        match (ties.hook, ties.kind) {
+            (None, _)
+            | (Some(TieHook::Corner(_)), StirrupKind::Closed | StirrupKind::Overlapping)
+            | (Some(TieHook::Angle(90.0 | 135.0)), StirrupKind::Cross) => {}
+            (Some(TieHook::Angle(angle)), StirrupKind::Cross) => {
+                return Err((ties.line, SemanticError::HookAngle(angle)));
+            }
+            (Some(hook), kind) => {
+                let error = SemanticError::HookNotApplicable {
+                    hook,
+                    kind: kind.keyword(),
+                };
+                return Err((ties.line, error));
+            }
+        }
+        for spacing in &ties.dist {
+            let dist = match *spacing {
+                Spacing::Fixed { count: 0, .. } => {
+                    return Err((ties.line, SemanticError::ZeroCount));
+                }
+                Spacing::Fixed { dist, .. } | Spacing::Rest { dist } => dist,
+            };
+            positive("spacing", dist, ties.line)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::parse;
+
+    fn error(input: &str) -> (usize, String) {
+        let error = parse(input).unwrap_err();
+        (error.line, error.message)
+    }
+
+    #[test]
+    fn test_rejects_counts() {
+        assert_eq!(
+            error("beam \"V-1\":\n  30 x 60\n  bot 2.7 #6\n"),
+            (3, "expected a whole number of bars, found 2.7".to_string())
+        );
+        assert_eq!(
+            error("beam \"V-1\":\n  30 x 60\n  bot -2 #6\n").1,
+            "expected a whole number of bars, found -2"
+        );
+        assert_eq!(
+            error("column \"C-1\":\n  D 40\n  perim 0 #6\n"),
+            (3, "expected at least one bar, found 0".to_string())
+        );
+        assert_eq!(
+            error("beam \"V-1\":\n  30 x 60\n  ties #3 0@10 rto@20\n"),
+            (3, "expected at least one bar, found 0".to_string())
+        );
+    }
+
+    #[test]
+    fn test_rejects_dimensions() {
+        assert_eq!(
+            error("beam \"V-1\":\n  30 x 0\n"),
+            (1, "height must be greater than zero, found 0".to_string())
+        );
+        assert_eq!(
+            error("set:\n  units mm\n\nbeam \"V-1\":\n  T 600 500 0 250\n"),
+            (
+                4,
+                "flange thickness must be greater than zero, found 0".to_string()
+            )
+        );
+        assert_eq!(
+            error("beam \"V-1\":\n  30 x 60\n  ties #3 rto@0\n"),
+            (3, "spacing must be greater than zero, found 0".to_string())
+        );
+        assert_eq!(
+            error("column \"C-1\":\n  D 50\n  spiral #3 pitch 0\n"),
+            (3, "pitch must be greater than zero, found 0".to_string())
+        );
+    }
+
+    #[test]
+    fn test_rejects_corner_count() {
+        assert_eq!(
+            error("column \"C-1\":\n  40 x 40\n  corners 2 #8 1 #6\n"),
+            (3, "`corners` takes 4 bars, found 3".to_string())
+        );
+        assert!(parse("column \"C-1\":\n  40 x 40\n  corners 2 #8 2 #6\n").is_ok());
+    }
+
+    #[test]
+    fn test_rejects_spaced_lines() {
+        assert!(parse("wall \"M-1\":\n  100 x 20\n  top #4 @ 20\n").is_ok());
+        assert_eq!(
+            error("wall \"M-1\":\n  100 x 20\n  sides #4 @ 20\n"),
+            (
+                3,
+                "`sides` bars cannot be given by spacing, only `top`, `bot` and `mid`".to_string()
+            )
+        );
+        assert_eq!(
+            error("wall \"M-1\":\n  100 x 20\n  bot 1/2\" @ 0\n"),
+            (3, "spacing must be greater than zero, found 0".to_string())
+        );
+    }
+
+    #[test]
+    fn test_rejects_tie_layout() {
+        let column = "column \"C-1\":\n  40 x 40\n  ties:\n";
+        assert!(
+            parse(&format!(
+                "{}    outer #3 rto@15\n    inner cross #3 rto@30\n",
+                column
+            ))
+            .is_ok()
+        );
+        assert_eq!(
+            error(&format!("{}    inner diamond #3 rto@15\n", column)),
+            (
+                4,
+                "`inner` ties need an `outer` tie around them".to_string()
+            )
+        );
+        assert_eq!(
+            error(&format!(
+                "{}    outer #3 rto@15\n    outer open-top #3 rto@15\n",
+                column
+            )),
+            (5, "expected one `outer` tie, found 2".to_string())
+        );
+    }
+
+    #[test]
+    fn test_rejects_hooks() {
+        let column = "column \"C-1\":\n  40 x 40\n  ties:\n    outer #3 rto@15 hook bottom-right\n";
+        assert!(parse(&format!("{}    inner cross #3 rto@30 hook 135\n", column)).is_ok());
+        assert_eq!(
+            error(&format!("{}    inner cross #3 rto@30 hook 120\n", column)),
+            (
+                5,
+                "expected a hook of 90 or 135 degrees, found 120".to_string()
+            )
+        );
+        assert_eq!(
+            error(&format!(
+                "{}    inner diamond #3 rto@30 hook top-left\n",
+                column
+            )),
+            (
+                5,
+                "`hook top-left` does not apply to `inner diamond` ties".to_string()
+            )
+        );
+        assert_eq!(
+            error("beam \"V-1\":\n  30 x 60\n  ties #3 rto@20 hook 90\n").1,
+            "`hook 90` does not apply to `outer` ties"
+        );
+    }
+
+    #[test]
+    fn test_rejects_polygons() {
+        let shape = "beam \"V-1\":\n  shape:\n    polygon:\n      (0, 0)\n      (40, 0)\n";
+        assert_eq!(
+            error(&format!("{}  cover 4\n", shape)),
+            (
+                1,
+                "a polygon needs at least 3 vertices, found 2".to_string()
+            )
+        );
+        let void =
+            "    void:\n      polygon:\n        (10, 10)\n        (20, 20)\n        (30, 30)\n";
+        assert_eq!(
+            error(&format!("{}      (40, 60)\n{}", shape, void)),
+            (1, "the void encloses no area".to_string())
+        );
+
+        // Edges with no length, at the vertex that repeats
+        assert_eq!(
+            error(&format!("{}      (40, 0)\n      (40, 60)\n", shape)),
+            (6, "the vertex repeats the one before it".to_string())
+        );
+        assert_eq!(
+            error(&format!("{}      (40, 60)\n      (0, 0)\n", shape)),
+            (
+                7,
+                "the last vertex repeats the first; a polygon closes on its own".to_string()
+            )
+        );
+
+        // Voids within the outline, touching it at most
+        let square = |x: f64| {
+            format!(
+                "      polygon:\n        ({x}, 10)\n        ({}, 10)\n        ({}, 30)\n        ({x}, 30)\n",
+                x + 20.0,
+                x + 20.0
+            )
+        };
+        let outline = format!("{}      (40, 60)\n      (0, 60)\n", shape);
+        assert!(parse(&format!("{}    void:\n{}", outline, square(20.0))).is_ok());
+        assert_eq!(
+            error(&format!("{}    void:\n{}", outline, square(50.0))),
+            (8, "the void is not inside the polygon".to_string())
+        );
+    }
+
+    #[test]
+    fn test_rejects_layers() {
+        let beam = "beam \"V-1\":\n  30 x 60\n  bot 3 #8\n";
+        assert!(parse(&format!("{}  layer bot 2:\n    2 #6\n", beam)).is_ok());
+        assert_eq!(
+            error(&format!("{}  layer bot 3:\n    2 #6\n", beam)),
+            (
+                4,
+                "expected `bot` layer 2, found layer 3; layers are numbered from 1 at the stirrup"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            error(&format!("{}  layer bot 1:\n    2 #6\n", beam)).1,
+            "expected `bot` layer 2, found layer 1; layers are numbered from 1 at the stirrup"
+        );
+    }
+
+    #[test]
+    fn test_rejects_cover() {
+        // Inherited from `set:`, reported at the `cover` line there
+        let input = "set:\n  cover 12\n\nbeam \"V-1\":\n  30 x 60\nbeam \"V-2\":\n  20 x 40\n";
+        assert_eq!(
+            error(input),
+            (
+                2,
+                "cover 12 leaves no room for bars, half the section is 10".to_string()
+            )
+        );
+        // A tee is limited by its web
+        assert!(parse("beam \"V-1\":\n  T 60 50 10 20\n  cover 12\n").is_err());
+        assert!(parse("beam \"V-1\":\n  T 60 50 10 30\n  cover 12\n").is_ok());
+
+        // Per face, the top and bottom covers share the height
+        let wall = "wall \"M-1\":\n  100 x 20\n";
+        assert!(parse(&format!("{}  cover top 2.5 bot 12 sides 4\n", wall)).is_ok());
+        assert_eq!(
+            error(&format!("{}  cover 4 bot 16\n", wall)),
+            (
+                3,
+                "cover top 4 bot 16 leaves no room for bars, the section is 20 high".to_string()
+            )
+        );
+        assert_eq!(
+            error(&format!("{}  cover top 2.5 bot 5\n", wall)),
+            (
+                3,
+                "no cover for `sides`; give every face, or a value for the rest first".to_string()
+            )
+        );
+        assert_eq!(
+            error("column \"C-1\":\n  D 50\n  cover 4 bot 5\n"),
+            (
+                3,
+                "a circular section takes a single `cover` value".to_string()
+            )
+        );
+    }
+}
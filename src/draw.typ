@@ -70,7 +70,41 @@
               name: primitive.group,
             )
           } else if primitive.type == "Text" {
-            cetz.draw.content((primitive.x, primitive.y), [*#primitive.content*])
+            let anchor = if primitive.at("anchor", default: none) != none { primitive.anchor } else { "center" }
+            cetz.draw.content((primitive.x, primitive.y), anchor: anchor, text(size: 8pt, primitive.content))
+          } else if primitive.type == "Dimension" {
+            let stroke = if primitive.stroke != none {
+              (paint: parse-color(primitive.stroke.color), thickness: primitive.stroke.width * 1pt)
+            } else {
+              black + 0.5pt
+            }
+            let (x1, y1) = primitive.start
+            let (x2, y2) = primitive.end
+            let length = calc.sqrt(calc.pow(x2 - x1, 2) + calc.pow(y2 - y1, 2))
+
+            if length > 0 {
+              // Left-hand normal of the measured direction
+              let (nx, ny) = (-(y2 - y1) / length, (x2 - x1) / length)
+              let o = primitive.offset
+              let p1 = (x1 + nx * o, y1 + ny * o)
+              let p2 = (x2 + nx * o, y2 + ny * o)
+
+              // Extension lines run past the dimension line by 1 unit
+              if o != 0 {
+                let over = if o > 0 { 1 } else { -1 }
+                cetz.draw.line((x1, y1), (p1.at(0) + nx * over, p1.at(1) + ny * over), stroke: stroke)
+                cetz.draw.line((x2, y2), (p2.at(0) + nx * over, p2.at(1) + ny * over), stroke: stroke)
+              }
+
+              cetz.draw.line(p1, p2, stroke: stroke, mark: (start: ">", end: ">", fill: stroke.paint))
+              cetz.draw.content(
+                ((p1.at(0) + p2.at(0)) / 2, (p1.at(1) + p2.at(1)) / 2),
+                angle: calc.atan2(x2 - x1, y2 - y1),
+                anchor: "south",
+                padding: 1pt,
+                text(size: 8pt, primitive.text),
+              )
+            }
           }
         }
       }))
@@ -84,3 +118,35 @@
     )
   }
 }
+
+#let draw-error(error) = {
+  block(
+    width: 100%,
+    fill: rgb("#fde8e8"),
+    stroke: 1pt + rgb("#CC0000"),
+    inset: 0.5em,
+    {
+      strong[line #error.line: #error.message]
+      linebreak()
+      raw(str(error.line) + " | " + error.snippet)
+      linebreak()
+      raw(" " * (str(error.line).len() + 3 + error.column - 1) + "^")
+    },
+  )
+}
+
+#let draw-warnings(warnings) = {
+  block(
+    width: 100%,
+    fill: rgb("#fff8e1"),
+    stroke: 1pt + rgb("#E6A100"),
+    inset: 0.5em,
+    {
+      for warning in warnings {
+        let label = if warning.severity == "info" { "info" } else { "warning" }
+        [*#label* (#warning.section, line #warning.line): #warning.message #h(0.5em) #raw(warning.code)]
+        linebreak()
+      }
+    },
+  )
+}
@@ -1,8 +1,12 @@
 #import plugin("parser.wasm"): priv_parse_and_generate
-#import "draw.typ": draw
+#import "draw.typ": draw, draw-error, draw-warnings
 
 /// Parse an expression into a data structure.
-///  -> content
+/// Returns a dictionary with `status: "ok"`, the `drawings` and the
+/// `warnings` (each with `severity`, `code`, `message`, `section` and
+/// `line`), or `status: "error"` and an `error` with `line`, `column`,
+/// `snippet`, `expected` and `message`.
+///  -> dictionary
 #let parse(
   /// The expression to parse.
   expr,
@@ -11,7 +15,20 @@
   data
 }
 
-#let init_rcsection(body, ..options) = {
-  show raw.where(lang: "rcs"): it => draw(parse(it.text))
+/// With `draft: true`, warnings are shown in a box under each drawing. Other
+/// options are accepted and left for later use.
+#let init_rcsection(body, draft: false, ..options) = {
+  show raw.where(lang: "rcs"): it => {
+    let result = parse(it.text)
+    if result.status == "error" {
+      draw-error(result.error)
+    } else {
+      draw(result.drawings)
+      let warnings = result.at("warnings", default: ())
+      if draft and warnings.len() > 0 {
+        draw-warnings(warnings)
+      }
+    }
+  }
   body
 }